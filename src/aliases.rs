@@ -0,0 +1,182 @@
+//! Cross-ecosystem package name aliases
+//!
+//! The same tool goes by different names in different package managers
+//! (`fd` is `fd-find` on apt, `node` is `nodejs`) and sometimes installs a
+//! differently named executable (`batcat`). A bundled table covers common
+//! cases; `~/.config/1install/aliases.toml` extends or overrides it.
+//!
+//! ```toml
+//! [fd]                                  # canonical name
+//! names = { apt = "fd-find" }           # native package name per backend
+//! binaries = { apt = "fdfind" }         # executable name per backend
+//!
+//! [ripgrep]
+//! command = "rg"                        # shim name (defaults to the key)
+//! aka = ["rg"]                          # other names people type
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use crate::config::get_config_path;
+use crate::error::OneInstallError;
+
+/// Aliases shipped with 1install
+const BUNDLED: &str = include_str!("aliases.toml");
+
+/// Everything known about one package's names
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Alias {
+    /// Command the shim is created under (defaults to the canonical name)
+    pub command: Option<String>,
+    /// Native package name per backend, where it differs
+    pub names: BTreeMap<String, String>,
+    /// Executable name per backend, where it differs from the command
+    pub binaries: BTreeMap<String, String>,
+    /// Other names people type for this package
+    pub aka: Vec<String>,
+}
+
+/// Alias table keyed by canonical name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AliasDb {
+    entries: BTreeMap<String, Alias>,
+}
+
+impl AliasDb {
+    /// The aliases shipped with 1install
+    pub fn bundled() -> Self {
+        Self::parse(BUNDLED).expect("bundled aliases.toml is valid")
+    }
+
+    /// Bundled aliases extended by the user's file, if there is one
+    pub fn load() -> Result<Self, OneInstallError> {
+        let mut db = Self::bundled();
+        let path = Self::user_path();
+        if path.exists() {
+            db.merge(Self::parse(&fs::read_to_string(&path)?)?);
+        }
+        Ok(db)
+    }
+
+    /// Parse an alias table
+    pub fn parse(content: &str) -> Result<Self, OneInstallError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Location of the user's alias file
+    pub fn user_path() -> PathBuf {
+        get_config_path().with_file_name("aliases.toml")
+    }
+
+    /// Layer another table on top; its fields win where both set one
+    pub fn merge(&mut self, other: AliasDb) {
+        for (key, alias) in other.entries {
+            let entry = self.entries.entry(key).or_default();
+            if alias.command.is_some() {
+                entry.command = alias.command;
+            }
+            entry.names.extend(alias.names);
+            entry.binaries.extend(alias.binaries);
+            for name in alias.aka {
+                if !entry.aka.contains(&name) {
+                    entry.aka.push(name);
+                }
+            }
+        }
+    }
+
+    /// Canonical name for whatever the user typed (`nodejs` → `node`)
+    pub fn canonical<'a>(&'a self, name: &'a str) -> &'a str {
+        self.lookup(name).map_or(name, |(key, _)| key)
+    }
+
+    /// Package name to hand to a backend (`fd` → `fd-find` on apt)
+    pub fn package_for(&self, name: &str, backend: &str) -> String {
+        match self.lookup(name) {
+            Some((key, alias)) => alias.names.get(backend).unwrap_or(key).clone(),
+            None => name.to_string(),
+        }
+    }
+
+    /// Command a package's shim is created under (`ripgrep` → `rg`)
+    pub fn command_for(&self, name: &str) -> String {
+        match self.lookup(name) {
+            Some((key, alias)) => alias.command.as_ref().unwrap_or(key).clone(),
+            None => name.to_string(),
+        }
+    }
+
+    /// Executable a backend's package puts on PATH (`bat` → `batcat` on apt)
+    pub fn binary_for(&self, name: &str, backend: &str) -> String {
+        match self.lookup(name).and_then(|(_, alias)| alias.binaries.get(backend)) {
+            Some(binary) => binary.clone(),
+            None => self.command_for(name),
+        }
+    }
+
+    /// Every name a package goes by, canonical first
+    pub fn names_for(&self, name: &str) -> Vec<String> {
+        let Some((key, alias)) = self.lookup(name) else {
+            return vec![name.to_string()];
+        };
+        let mut names = vec![key.clone()];
+        for other in alias.aka.iter().chain(alias.names.values()).chain(&alias.command) {
+            if !names.contains(other) {
+                names.push(other.clone());
+            }
+        }
+        names
+    }
+
+    /// Entry a name belongs to, by key, `aka` or native name
+    fn lookup(&self, name: &str) -> Option<(&String, &Alias)> {
+        let name = name.to_lowercase();
+        self.entries.get_key_value(&name).or_else(|| {
+            self.entries.iter().find(|(_, alias)| {
+                alias.aka.iter().chain(alias.names.values()).any(|n| n.to_lowercase() == name)
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_names() {
+        let db = AliasDb::bundled();
+        assert_eq!(db.package_for("fd", "apt"), "fd-find");
+        assert_eq!(db.package_for("fd", "brew"), "fd");
+        assert_eq!(db.package_for("fd-find", "pacman"), "fd");
+        assert_eq!(db.package_for("nodejs", "brew"), "node");
+        assert_eq!(db.package_for("jq", "apt"), "jq");
+
+        assert_eq!(db.canonical("python3"), "python");
+        assert_eq!(db.command_for("fd-find"), "fd");
+        assert_eq!(db.binary_for("fd-find", "apt"), "fdfind");
+        assert_eq!(db.binary_for("ripgrep", "apt"), "rg");
+        assert_eq!(db.binary_for("bat", "brew"), "bat");
+        assert!(db.names_for("fd").contains(&"fd-find".to_string()));
+    }
+
+    #[test]
+    fn test_user_entries_extend_bundled() {
+        let mut db = AliasDb::bundled();
+        db.merge(AliasDb::parse(r#"
+            [fd]
+            names = { apt = "fd" }
+
+            [kubectl]
+            names = { snap = "kubectl", apt = "kubernetes-client" }
+        "#).unwrap());
+
+        assert_eq!(db.package_for("fd", "apt"), "fd");
+        assert_eq!(db.binary_for("fd", "apt"), "fdfind");
+        assert_eq!(db.package_for("kubectl", "apt"), "kubernetes-client");
+    }
+}
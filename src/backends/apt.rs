@@ -1,8 +1,13 @@
 //! APT backend for Debian-based Linux distributions
 
-use super::{Backend, command_exists, run_command_output};
+use super::{
+    Backend, BackendCommand, OutdatedPackage, PackageInfo,
+    command_exists, run_command, run_command_output, parse_fields, parse_path_lines, range_unsupported,
+    VersionReq,
+};
 use crate::search::PackageResult;
-use std::process::Command;
+use crate::error::OneInstallError;
+use std::path::PathBuf;
 
 /// APT package manager backend (Debian, Ubuntu, etc.)
 pub struct AptBackend;
@@ -31,6 +36,65 @@ impl AptBackend {
             })
             .collect()
     }
+    
+    /// Parse dpkg-query output ("status<TAB>name<TAB>version") into PackageResults
+    fn parse_installed_output(&self, output: &str) -> Vec<PackageResult> {
+        output.lines()
+            .filter_map(|line| {
+                let mut parts = line.split('\t');
+                let status = parts.next()?;
+                let name = parts.next()?.trim();
+                let version = parts.next()?.trim();
+                // Skip packages that were removed but left config files behind
+                if !status.starts_with("ii") || name.is_empty() {
+                    return None;
+                }
+                Some(PackageResult::new(name.to_string(), "apt".to_string()).with_version(version))
+            })
+            .collect()
+    }
+    
+    /// Parse `apt list --upgradable` output
+    /// ("jq/stable 1.7.1-3 amd64 [upgradable from: 1.6-2.1]")
+    fn parse_outdated_output(&self, output: &str) -> Vec<OutdatedPackage> {
+        output.lines()
+            .filter(|line| line.contains("[upgradable from:"))
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let name = parts.next()?.split('/').next()?;
+                let latest = parts.next()?;
+                let current = line.rsplit("upgradable from: ").next()?.trim_end_matches(']');
+                Some(
+                    OutdatedPackage::new(name, "apt")
+                        .with_current(current)
+                        .with_latest(latest)
+                )
+            })
+            .collect()
+    }
+    
+    /// Parse the first stanza of `apt-cache show` output into a PackageInfo
+    fn parse_show_output(&self, output: &str, package: &str) -> PackageInfo {
+        let stanza = output.split("\n\n").next().unwrap_or("");
+        let fields = parse_fields(stanza);
+        let field = |key: &str| fields.get(key).filter(|v| !v.is_empty()).cloned();
+        
+        let mut info = PackageInfo::new(field("Package").unwrap_or_else(|| package.to_string()), "apt");
+        info.candidate_version = field("Version");
+        info.description = field("Description").or_else(|| field("Description-en"));
+        info.homepage = field("Homepage");
+        info.maintainer = field("Maintainer");
+        info.size = field("Installed-Size").map(|kb| format!("{} kB installed", kb));
+        info.dependencies = field("Depends")
+            .map(|deps| {
+                deps.split(',')
+                    .filter_map(|dep| dep.split_whitespace().next())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        info
+    }
 }
 
 impl Default for AptBackend {
@@ -48,93 +112,98 @@ impl Backend for AptBackend {
         command_exists("apt-cache")
     }
     
-    fn search(&self, query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>> {
+    fn needs_root(&self) -> bool {
+        true
+    }
+    
+    fn search(&self, query: &str) -> Result<Vec<PackageResult>, OneInstallError> {
         let output = run_command_output("apt-cache", &["search", query])?;
         Ok(self.parse_search_output(&output))
     }
     
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let need_sudo = !is_root();
-        
-        let cmd_display = if need_sudo {
-            format!("sudo apt-get install -y {}", package)
-        } else {
-            format!("apt-get install -y {}", package)
-        };
-        
-        println!("   Running: {}", cmd_display);
-        
-        let status = if need_sudo {
-            Command::new("sudo")
-                .args(["apt-get", "install", "-y", package])
-                .env("DEBIAN_FRONTEND", "noninteractive")
-                .status()?
-        } else {
-            Command::new("apt-get")
-                .args(["install", "-y", package])
-                .env("DEBIAN_FRONTEND", "noninteractive")
-                .status()?
-        };
-        
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("apt-get install failed with exit code: {:?}", status.code()).into())
-        }
+    fn install(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&apt_get(["install", "-y", package]))
     }
 
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let need_sudo = !is_root();
-        
-        if need_sudo {
-            println!("   Running: sudo apt-get update && sudo apt-get install --only-upgrade -y {}", package);
-            Command::new("sudo").args(["apt-get", "update"]).status()?;
-            let status = Command::new("sudo")
-                .args(["apt-get", "install", "--only-upgrade", "-y", package])
-                .env("DEBIAN_FRONTEND", "noninteractive")
-                .status()?;
-            if status.success() { Ok(()) } else { Err(format!("apt-get update failed: {:?}", status.code()).into()) }
-        } else {
-            println!("   Running: apt-get update && apt-get install --only-upgrade -y {}", package);
-            Command::new("apt-get").args(["apt-get", "update"]).status()?;
-            let status = Command::new("apt-get")
-                .args(["install", "--only-upgrade", "-y", package])
-                .env("DEBIAN_FRONTEND", "noninteractive")
-                .status()?;
-            if status.success() { Ok(()) } else { Err(format!("apt-get update failed: {:?}", status.code()).into()) }
+    fn install_many(&self, packages: &[&str]) -> Result<(), OneInstallError> {
+        run_command(&apt_get(["install", "-y"].into_iter().chain(packages.iter().copied())))
+    }
+
+    fn install_version(&self, package: &str, version: &str) -> Result<(), OneInstallError> {
+        let pinned = format!("{}={}", package, version);
+        run_command(&apt_get(["install", "-y", "--allow-downgrades", &pinned]))
+    }
+
+    fn install_matching(&self, package: &str, version: &VersionReq) -> Result<(), OneInstallError> {
+        match version {
+            VersionReq::Exact(version) => self.install_version(package, version),
+            // apt-get matches `name=glob` against candidate versions
+            VersionReq::Prefix(prefix) => {
+                let pinned = format!("{}={}.*", package, prefix);
+                run_command(&apt_get(["install", "-y", "--allow-downgrades", &pinned]))
+            }
+            range => Err(range_unsupported(self.name(), range)),
         }
     }
 
-    fn uninstall(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        let need_sudo = !is_root();
+    fn update(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("apt-get").arg("update").sudo())?;
+        run_command(&apt_get(["install", "--only-upgrade", "-y", package]))
+    }
+
+    fn uninstall(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&apt_get(["remove", "-y", package]))
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, OneInstallError> {
+        let output = run_command_output(
+            "dpkg-query",
+            &["-W", "-f=${db:Status-Abbrev}\\t${Package}\\t${Version}\\n"],
+        )?;
+        Ok(self.parse_installed_output(&output))
+    }
+
+    fn outdated(&self) -> Result<Vec<OutdatedPackage>, OneInstallError> {
+        let output = run_command_output("apt", &["list", "--upgradable"])?;
+        Ok(self.parse_outdated_output(&output))
+    }
+
+    fn upgrade_all(&self) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("apt-get").arg("update").sudo())?;
+        run_command(&apt_get(["upgrade", "-y"]))
+    }
+
+    fn installed_files(&self, package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        let output = run_command_output("dpkg", &["-L", package])?;
+        Ok(parse_path_lines(&output))
+    }
+
+    fn info(&self, package: &str) -> Result<Option<PackageInfo>, OneInstallError> {
+        // apt-cache exits non-zero when the package is unknown
+        let show = match run_command_output("apt-cache", &["show", package]) {
+            Ok(output) if !output.trim().is_empty() => output,
+            _ => return Ok(None),
+        };
+        let mut info = self.parse_show_output(&show, package);
         
-        if need_sudo {
-            println!("   Running: sudo apt-get remove -y {}", package);
-            let status = Command::new("sudo")
-                .args(["apt-get", "remove", "-y", package])
-                .status()?;
-            if status.success() { Ok(()) } else { Err(format!("apt-get remove failed: {:?}", status.code()).into()) }
-        } else {
-            println!("   Running: apt-get remove -y {}", package);
-            let status = Command::new("apt-get")
-                .args(["remove", "-y", package])
-                .status()?;
-            if status.success() { Ok(()) } else { Err(format!("apt-get remove failed: {:?}", status.code()).into()) }
+        if let Ok(policy) = run_command_output("apt-cache", &["policy", package]) {
+            let fields = parse_fields(&policy);
+            info.installed_version = fields.get("Installed").filter(|v| *v != "(none)").cloned();
+            if let Some(candidate) = fields.get("Candidate").filter(|v| *v != "(none)") {
+                info.candidate_version = Some(candidate.clone());
+            }
         }
+        
+        Ok(Some(info))
     }
 }
 
-/// Check if running as root
-fn is_root() -> bool {
-    #[cfg(unix)]
-    {
-        unsafe { libc::geteuid() == 0 }
-    }
-    
-    #[cfg(not(unix))]
-    {
-        false
-    }
+/// Build a non-interactive, privileged apt-get invocation
+fn apt_get<'a>(args: impl IntoIterator<Item = &'a str>) -> BackendCommand {
+    BackendCommand::new("apt-get")
+        .args(args)
+        .env("DEBIAN_FRONTEND", "noninteractive")
+        .sudo()
 }
 
 #[cfg(test)]
@@ -157,4 +226,41 @@ mod tests {
         assert_eq!(results[0].name, "python3");
         assert_eq!(results[1].name, "python3-pip");
     }
+    
+    #[test]
+    fn test_parse_installed_output() {
+        let backend = AptBackend::new();
+        let output = "ii \tjq\t1.6-2.1\nrc \told-tool\t0.1\nii \tripgrep\t13.0.0-4\n";
+        let results = backend.parse_installed_output(output);
+        
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].name, "jq");
+        assert_eq!(results[0].version.as_deref(), Some("1.6-2.1"));
+        assert_eq!(results[1].name, "ripgrep");
+    }
+    
+    #[test]
+    fn test_parse_outdated_output() {
+        let backend = AptBackend::new();
+        let output = "Listing... Done\njq/stable 1.7.1-3 amd64 [upgradable from: 1.6-2.1]\n";
+        let results = backend.parse_outdated_output(output);
+        
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "jq");
+        assert_eq!(results[0].current.as_deref(), Some("1.6-2.1"));
+        assert_eq!(results[0].latest.as_deref(), Some("1.7.1-3"));
+    }
+    
+    #[test]
+    fn test_parse_show_output() {
+        let backend = AptBackend::new();
+        let output = "Package: jq\nVersion: 1.6-2.1\nInstalled-Size: 110\nMaintainer: Jane <j@example.com>\n\
+                      Depends: libjq1 (= 1.6-2.1), libc6 (>= 2.34)\nDescription: JSON processor\n\
+                      Homepage: https://github.com/stedolan/jq\n\nPackage: jq\nVersion: 1.5\n";
+        let info = backend.parse_show_output(output, "jq");
+        
+        assert_eq!(info.candidate_version.as_deref(), Some("1.6-2.1"));
+        assert_eq!(info.homepage.as_deref(), Some("https://github.com/stedolan/jq"));
+        assert_eq!(info.dependencies, vec!["libjq1", "libc6"]);
+    }
 }
@@ -1,8 +1,12 @@
 //! Homebrew backend for macOS (and Linux)
 
-use super::{Backend, command_exists, run_command_output};
+use super::{
+    Backend, BackendCommand, OutdatedPackage, PackageInfo,
+    command_exists, run_command, run_command_output, json_str, executables_in_dir, parse_path_lines,
+};
 use crate::search::PackageResult;
-use std::process::Command;
+use crate::error::OneInstallError;
+use std::path::{Path, PathBuf};
 
 /// Homebrew package manager backend
 pub struct BrewBackend;
@@ -22,6 +26,80 @@ impl BrewBackend {
             })
             .collect()
     }
+    
+    /// Parse `brew list --versions` output ("name version [version...]")
+    fn parse_installed_output(&self, output: &str) -> Vec<PackageResult> {
+        output.lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let name = parts.next()?.to_string();
+                // When several versions are installed the newest is listed last
+                let mut result = PackageResult::new(name, "brew".to_string());
+                if let Some(version) = parts.last() {
+                    result = result.with_version(version);
+                }
+                Some(result)
+            })
+            .collect()
+    }
+    
+    /// Parse `brew outdated --json=v2` output (formulae and casks)
+    fn parse_outdated_output(&self, output: &str) -> Vec<OutdatedPackage> {
+        let json: serde_json::Value = match serde_json::from_str(output) {
+            Ok(v) => v,
+            Err(_) => return Vec::new(),
+        };
+        
+        ["formulae", "casks"].iter()
+            .filter_map(|kind| json.get(kind).and_then(|v| v.as_array()))
+            .flatten()
+            .filter_map(|item| {
+                let name = item.get("name")?.as_str()?;
+                let mut package = OutdatedPackage::new(name, "brew");
+                let installed = item.get("installed_versions")
+                    .and_then(|v| v.as_array())
+                    .and_then(|v| v.last())
+                    .and_then(|v| v.as_str());
+                if let Some(current) = installed {
+                    package = package.with_current(current);
+                }
+                if let Some(latest) = item.get("current_version").and_then(|v| v.as_str()) {
+                    package = package.with_latest(latest);
+                }
+                Some(package)
+            })
+            .collect()
+    }
+    
+    /// Parse `brew info --json=v2` output (first formula or cask)
+    fn parse_info_output(&self, output: &str) -> Option<PackageInfo> {
+        let json: serde_json::Value = serde_json::from_str(output).ok()?;
+        
+        if let Some(formula) = json.pointer("/formulae/0") {
+            let mut info = PackageInfo::new(json_str(formula, "/name")?, "brew");
+            info.description = json_str(formula, "/desc");
+            info.homepage = json_str(formula, "/homepage");
+            info.license = json_str(formula, "/license");
+            info.candidate_version = json_str(formula, "/versions/stable");
+            info.installed_version = formula.get("installed")
+                .and_then(|v| v.as_array())
+                .and_then(|v| v.last())
+                .and_then(|v| json_str(v, "/version"));
+            info.dependencies = formula.get("dependencies")
+                .and_then(|v| v.as_array())
+                .map(|deps| deps.iter().filter_map(|d| d.as_str().map(String::from)).collect())
+                .unwrap_or_default();
+            return Some(info);
+        }
+        
+        let cask = json.pointer("/casks/0")?;
+        let mut info = PackageInfo::new(json_str(cask, "/token")?, "brew");
+        info.description = json_str(cask, "/desc");
+        info.homepage = json_str(cask, "/homepage");
+        info.candidate_version = json_str(cask, "/version");
+        info.installed_version = json_str(cask, "/installed");
+        Some(info)
+    }
 }
 
 impl Default for BrewBackend {
@@ -39,50 +117,56 @@ impl Backend for BrewBackend {
         command_exists("brew")
     }
     
-    fn search(&self, query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>> {
+    fn search(&self, query: &str) -> Result<Vec<PackageResult>, OneInstallError> {
         let output = run_command_output("brew", &["search", query])?;
         Ok(self.parse_search_output(&output))
     }
     
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: brew install {}", package);
-        
-        let status = Command::new("brew")
-            .args(["install", package])
-            .status()?;
-        
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("brew install failed with exit code: {:?}", status.code()).into())
-        }
+    fn install(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("brew").args(["install", package]))
     }
 
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: brew upgrade {}", package);
-        
-        let status = Command::new("brew")
-            .args(["upgrade", package])
-            .status()?;
-        
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("brew upgrade failed with exit code: {:?}", status.code()).into())
-        }
+    fn install_many(&self, packages: &[&str]) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("brew").arg("install").args(packages.iter().copied()))
     }
 
-    fn uninstall(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: brew uninstall {}", package);
-        
-        let status = Command::new("brew")
-            .args(["uninstall", package])
-            .status()?;
-        
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("brew uninstall failed with exit code: {:?}", status.code()).into())
+    fn update(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("brew").args(["upgrade", package]))
+    }
+
+    fn uninstall(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("brew").args(["uninstall", package]))
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, OneInstallError> {
+        let output = run_command_output("brew", &["list", "--versions"])?;
+        Ok(self.parse_installed_output(&output))
+    }
+
+    fn outdated(&self) -> Result<Vec<OutdatedPackage>, OneInstallError> {
+        let output = run_command_output("brew", &["outdated", "--json=v2"])?;
+        Ok(self.parse_outdated_output(&output))
+    }
+
+    fn upgrade_all(&self) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("brew").arg("upgrade"))
+    }
+
+    fn installed_files(&self, package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        let output = run_command_output("brew", &["list", package])?;
+        Ok(parse_path_lines(&output))
+    }
+
+    fn binaries(&self, package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        // The opt/ prefix survives upgrades; the Cellar paths in `brew list` don't
+        let prefix = run_command_output("brew", &["--prefix", package])?;
+        Ok(executables_in_dir(&Path::new(prefix.trim()).join("bin")))
+    }
+
+    fn info(&self, package: &str) -> Result<Option<PackageInfo>, OneInstallError> {
+        match run_command_output("brew", &["info", "--json=v2", package]) {
+            Ok(output) => Ok(self.parse_info_output(&output)),
+            Err(_) => Ok(None),
         }
     }
 }
@@ -96,4 +180,39 @@ mod tests {
         let backend = BrewBackend::new();
         assert_eq!(backend.name(), "brew");
     }
+    
+    #[test]
+    fn test_parse_installed_output() {
+        let backend = BrewBackend::new();
+        let results = backend.parse_installed_output("jq 1.7.1\nnode 20.1.0 21.2.0\n");
+        
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].version.as_deref(), Some("1.7.1"));
+        assert_eq!(results[1].version.as_deref(), Some("21.2.0"));
+    }
+    
+    #[test]
+    fn test_parse_outdated_output() {
+        let backend = BrewBackend::new();
+        let output = r#"{"formulae":[{"name":"jq","installed_versions":["1.6"],"current_version":"1.7.1"}],"casks":[]}"#;
+        let results = backend.parse_outdated_output(output);
+        
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].current.as_deref(), Some("1.6"));
+        assert_eq!(results[0].latest.as_deref(), Some("1.7.1"));
+    }
+    
+    #[test]
+    fn test_parse_info_output() {
+        let backend = BrewBackend::new();
+        let output = r#"{"formulae":[{"name":"jq","desc":"JSON processor","license":"MIT",
+            "homepage":"https://jqlang.github.io/jq/","versions":{"stable":"1.7.1"},
+            "installed":[{"version":"1.7"}],"dependencies":["oniguruma"]}],"casks":[]}"#;
+        let info = backend.parse_info_output(output).unwrap();
+        
+        assert_eq!(info.license.as_deref(), Some("MIT"));
+        assert_eq!(info.installed_version.as_deref(), Some("1.7"));
+        assert_eq!(info.candidate_version.as_deref(), Some("1.7.1"));
+        assert_eq!(info.dependencies, vec!["oniguruma"]);
+    }
 }
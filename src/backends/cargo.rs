@@ -1,8 +1,12 @@
 //! Cargo backend for Rust tools
 
-use super::{Backend, command_exists};
+use super::{
+    Backend, BackendCommand, OutdatedPackage, PackageInfo,
+    command_exists, run_command, run_command_output, upgrade_outdated, VersionReq,
+};
 use crate::search::PackageResult;
-use std::process::Command;
+use crate::error::OneInstallError;
+use std::path::PathBuf;
 
 pub struct CargoBackend;
 
@@ -10,6 +14,39 @@ impl CargoBackend {
     pub fn new() -> Self {
         Self
     }
+    
+    /// Parse `cargo install --list` output ("name v1.2.3:" followed by indented binaries)
+    fn parse_installed_output(&self, output: &str) -> Vec<PackageResult> {
+        output.lines()
+            .filter(|line| !line.starts_with(char::is_whitespace))
+            .filter_map(|line| {
+                let line = line.trim_end_matches(':');
+                let mut parts = line.split_whitespace();
+                let name = parts.next()?.to_string();
+                let version = parts.next()?.trim_start_matches('v');
+                Some(PackageResult::new(name, "cargo".to_string()).with_version(version))
+            })
+            .collect()
+    }
+    
+    /// Binary names listed under one crate in `cargo install --list` output
+    fn parse_crate_binaries(&self, output: &str, package: &str) -> Vec<String> {
+        output.lines()
+            .skip_while(|line| line.split_whitespace().next() != Some(package))
+            .skip(1)
+            .take_while(|line| line.starts_with(char::is_whitespace))
+            .map(|line| line.trim().to_string())
+            .collect()
+    }
+    
+    /// Directory `cargo install` places binaries in
+    fn bin_dir(&self) -> Option<PathBuf> {
+        let root = std::env::var_os("CARGO_INSTALL_ROOT")
+            .or_else(|| std::env::var_os("CARGO_HOME"))
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")))?;
+        Some(root.join("bin"))
+    }
 }
 
 impl Default for CargoBackend {
@@ -27,13 +64,9 @@ impl Backend for CargoBackend {
         command_exists("cargo")
     }
     
-    fn search(&self, query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>> {
-        let output = Command::new("cargo")
-            .args(["search", query, "--limit", "10"])
-            .output()?;
-            
+    fn search(&self, query: &str) -> Result<Vec<PackageResult>, OneInstallError> {
+        let stdout = run_command_output("cargo", &["search", query, "--limit", "10"])?;
         let mut results = Vec::new();
-        let stdout = String::from_utf8_lossy(&output.stdout);
         
         for line in stdout.lines() {
             if line.contains(" = \"") {
@@ -62,35 +95,106 @@ impl Backend for CargoBackend {
         Ok(results)
     }
     
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: cargo install {}", package);
-        
-        let status = Command::new("cargo")
-            .args(["install", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("cargo install failed with exit code: {:?}", status.code()).into())
-        }
+    fn install(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("cargo").args(["install", package]))
     }
 
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fn install_many(&self, packages: &[&str]) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("cargo").arg("install").args(packages.iter().copied()))
+    }
+
+    fn install_version(&self, package: &str, version: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("cargo").args(["install", package, "--version", version]))
+    }
+
+    fn install_matching(&self, package: &str, version: &VersionReq) -> Result<(), OneInstallError> {
+        // `--version` accepts any semver requirement (`^1.2`, `1.2.*`, `>=1, <2`)
+        let requirement = match version {
+            VersionReq::Exact(version) => return self.install_version(package, version),
+            VersionReq::Prefix(prefix) => format!("{}.*", prefix),
+            VersionReq::Range(range) => range.clone(),
+        };
+        self.install_version(package, &requirement)
+    }
+
+    fn update(&self, package: &str) -> Result<(), OneInstallError> {
         self.install(package)
     }
 
-    fn uninstall(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: cargo uninstall {}", package);
-        
-        let status = Command::new("cargo")
-            .args(["uninstall", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("cargo uninstall failed with exit code: {:?}", status.code()).into())
+    fn uninstall(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("cargo").args(["uninstall", package]))
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, OneInstallError> {
+        let output = run_command_output("cargo", &["install", "--list"])?;
+        Ok(self.parse_installed_output(&output))
+    }
+
+    fn outdated(&self) -> Result<Vec<OutdatedPackage>, OneInstallError> {
+        // Same comparison cargo-install-update performs: installed vs. newest on crates.io
+        let mut outdated = Vec::new();
+        let mut last_error = None;
+        let mut checked = 0;
+        // A crate whose lookup fails is skipped rather than failing the
+        // whole check, unless every lookup fails
+        for installed in self.list_installed()? {
+            let found = match self.search(&installed.name) {
+                Ok(found) => found,
+                Err(e) => {
+                    last_error = Some(e);
+                    continue;
+                }
+            };
+            checked += 1;
+            let latest = found.into_iter()
+                .find(|r| r.name == installed.name)
+                .and_then(|r| r.version);
+            if let (Some(current), Some(latest)) = (installed.version, latest) {
+                if current != latest {
+                    outdated.push(
+                        OutdatedPackage::new(installed.name, "cargo")
+                            .with_current(current)
+                            .with_latest(latest)
+                    );
+                }
+            }
+        }
+        match last_error {
+            Some(e) if checked == 0 => Err(e),
+            _ => Ok(outdated),
         }
     }
+
+    fn upgrade_all(&self) -> Result<(), OneInstallError> {
+        upgrade_outdated(self)
+    }
+
+    fn binaries(&self, package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        let output = run_command_output("cargo", &["install", "--list"])?;
+        let Some(dir) = self.bin_dir() else {
+            return Ok(Vec::new());
+        };
+        Ok(self.parse_crate_binaries(&output, package).into_iter()
+            .map(|name| dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX)))
+            .filter(|path| path.is_file())
+            .collect())
+    }
+
+    fn info(&self, package: &str) -> Result<Option<PackageInfo>, OneInstallError> {
+        let found = match self.search(package)?.into_iter().find(|r| r.name == package) {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+        
+        let mut info = PackageInfo::new(found.name, "cargo");
+        info.candidate_version = found.version;
+        info.description = found.description.filter(|d| !d.is_empty());
+        info.homepage = Some(format!("https://crates.io/crates/{}", package));
+        info.installed_version = self.list_installed()?
+            .into_iter()
+            .find(|p| p.name == package)
+            .and_then(|p| p.version);
+        
+        Ok(Some(info))
+    }
 }
@@ -1,8 +1,13 @@
 //! DNF backend for Fedora/RHEL
 
-use super::{Backend, command_exists};
+use super::{
+    Backend, BackendCommand, OutdatedPackage, PackageInfo,
+    command_exists, runner, run_command, run_command_output, parse_fields, parse_path_lines,
+    range_unsupported, VersionReq,
+};
 use crate::search::PackageResult;
-use std::process::Command;
+use crate::error::OneInstallError;
+use std::path::PathBuf;
 
 pub struct DnfBackend;
 
@@ -10,6 +15,30 @@ impl DnfBackend {
     pub fn new() -> Self {
         Self
     }
+    
+    /// Parse `rpm -qa` output formatted as "name<TAB>version-release"
+    fn parse_installed_output(&self, output: &str) -> Vec<PackageResult> {
+        output.lines()
+            .filter_map(|line| {
+                let (name, version) = line.split_once('\t')?;
+                Some(PackageResult::new(name.trim().to_string(), "dnf".to_string()).with_version(version.trim()))
+            })
+            .collect()
+    }
+    
+    /// Parse `dnf check-update` output ("name.arch  version  repo")
+    fn parse_outdated_output(&self, output: &str) -> Vec<OutdatedPackage> {
+        output.lines()
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() != 3 || !parts[0].contains('.') {
+                    return None;
+                }
+                let name = parts[0].rsplit_once('.').map(|(n, _)| n).unwrap_or(parts[0]);
+                Some(OutdatedPackage::new(name, "dnf").with_latest(parts[1]))
+            })
+            .collect()
+    }
 }
 
 impl Default for DnfBackend {
@@ -27,13 +56,14 @@ impl Backend for DnfBackend {
         command_exists("dnf")
     }
     
-    fn search(&self, query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>> {
-        let output = Command::new("dnf")
-            .args(["search", query])
-            .output()?;
-            
+    fn needs_root(&self) -> bool {
+        true
+    }
+    
+    fn search(&self, query: &str) -> Result<Vec<PackageResult>, OneInstallError> {
+        // `dnf search` exits non-zero when nothing matches
+        let stdout = run_command_output("dnf", &["search", query]).unwrap_or_default();
         let mut results = Vec::new();
-        let stdout = String::from_utf8_lossy(&output.stdout);
         
         for line in stdout.lines() {
             if line.contains(" : ") {
@@ -55,45 +85,81 @@ impl Backend for DnfBackend {
         Ok(results)
     }
     
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: sudo dnf install -y {}", package);
-        
-        let status = Command::new("sudo")
-            .args(["dnf", "install", "-y", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("dnf install failed with exit code: {:?}", status.code()).into())
-        }
+    fn install(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("dnf").args(["install", "-y", package]).sudo())
     }
 
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: sudo dnf upgrade -y {}", package);
-        
-        let status = Command::new("sudo")
-            .args(["dnf", "upgrade", "-y", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("dnf upgrade failed with exit code: {:?}", status.code()).into())
+    fn install_many(&self, packages: &[&str]) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("dnf").args(["install", "-y"]).args(packages.iter().copied()).sudo())
+    }
+
+    fn install_version(&self, package: &str, version: &str) -> Result<(), OneInstallError> {
+        let pinned = format!("{}-{}", package, version);
+        run_command(&BackendCommand::new("dnf").args(["install", "-y", &pinned]).sudo())
+    }
+
+    fn install_matching(&self, package: &str, version: &VersionReq) -> Result<(), OneInstallError> {
+        match version {
+            VersionReq::Exact(version) => self.install_version(package, version),
+            VersionReq::Prefix(prefix) => {
+                let pinned = format!("{}-{}.*", package, prefix);
+                run_command(&BackendCommand::new("dnf").args(["install", "-y", &pinned]).sudo())
+            }
+            range => Err(range_unsupported(self.name(), range)),
         }
     }
 
-    fn uninstall(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: sudo dnf remove -y {}", package);
+    fn update(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("dnf").args(["upgrade", "-y", package]).sudo())
+    }
+
+    fn uninstall(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("dnf").args(["remove", "-y", package]).sudo())
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, OneInstallError> {
+        // rpm's query format is stable across dnf4 and dnf5
+        let output = run_command_output("rpm", &["-qa", "--qf", "%{NAME}\\t%{VERSION}-%{RELEASE}\\n"])?;
+        Ok(self.parse_installed_output(&output))
+    }
+
+    fn outdated(&self) -> Result<Vec<OutdatedPackage>, OneInstallError> {
+        // dnf check-update exits 100 when updates are available
+        let output = runner().output(&BackendCommand::new("dnf").args(["check-update", "-q"]).allow_exit_code(100))?;
+        Ok(self.parse_outdated_output(&output))
+    }
+
+    fn upgrade_all(&self) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("dnf").args(["upgrade", "-y"]).sudo())
+    }
+
+    fn installed_files(&self, package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        let output = run_command_output("rpm", &["-ql", package])?;
+        Ok(parse_path_lines(&output))
+    }
+
+    fn info(&self, package: &str) -> Result<Option<PackageInfo>, OneInstallError> {
+        let output = match run_command_output("dnf", &["info", "-q", package]) {
+            Ok(output) if !output.trim().is_empty() => output,
+            _ => return Ok(None),
+        };
+        let fields = parse_fields(&output);
+        let field = |key: &str| fields.get(key).filter(|v| !v.is_empty()).cloned();
         
-        let status = Command::new("sudo")
-            .args(["dnf", "remove", "-y", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("dnf remove failed with exit code: {:?}", status.code()).into())
-        }
+        let mut info = PackageInfo::new(field("Name").unwrap_or_else(|| package.to_string()), "dnf");
+        info.candidate_version = match (field("Version"), field("Release")) {
+            (Some(version), Some(release)) => Some(format!("{}-{}", version, release)),
+            (version, _) => version,
+        };
+        info.description = field("Summary");
+        info.homepage = field("URL");
+        info.license = field("License");
+        info.size = field("Size").or_else(|| field("Installed size"));
+        info.maintainer = field("Packager").or_else(|| field("Vendor"));
+        info.installed_version = run_command_output("rpm", &["-q", "--qf", "%{VERSION}-%{RELEASE}", package])
+            .ok()
+            .filter(|v| !v.trim().is_empty());
+        
+        Ok(Some(info))
     }
 }
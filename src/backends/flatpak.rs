@@ -1,8 +1,11 @@
 //! Flatpak backend for Linux
 
-use super::{Backend, command_exists};
+use super::{
+    Backend, BackendCommand, OutdatedPackage, PackageInfo,
+    command_exists, run_command, run_command_output, parse_fields,
+};
 use crate::search::PackageResult;
-use std::process::Command;
+use crate::error::OneInstallError;
 
 pub struct FlatpakBackend;
 
@@ -10,6 +13,41 @@ impl FlatpakBackend {
     pub fn new() -> Self {
         Self
     }
+    
+    /// Parse `flatpak list --columns=application,version` output
+    fn parse_installed_output(&self, output: &str) -> Vec<PackageResult> {
+        output.lines()
+            .filter_map(|line| {
+                let mut parts = line.split('\t');
+                let name = parts.next()?.trim();
+                if name.is_empty() {
+                    return None;
+                }
+                let mut result = PackageResult::new(name.to_string(), "flatpak".to_string());
+                if let Some(version) = parts.next().map(str::trim).filter(|v| !v.is_empty()) {
+                    result = result.with_version(version);
+                }
+                Some(result)
+            })
+            .collect()
+    }
+    
+    /// Parse `flatpak remote-info` output ("Name - Summary" line, then "Key: value" fields)
+    fn parse_info_output(&self, output: &str, package: &str) -> PackageInfo {
+        let fields = parse_fields(output);
+        let field = |key: &str| fields.get(key).filter(|v| !v.is_empty()).cloned();
+        
+        let mut info = PackageInfo::new(field("ID").unwrap_or_else(|| package.to_string()), "flatpak");
+        info.description = output.lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .and_then(|line| line.split_once(" - "))
+            .map(|(_, summary)| summary.to_string());
+        info.candidate_version = field("Version");
+        info.license = field("License");
+        info.size = field("Download").or_else(|| field("Installed"));
+        info
+    }
 }
 
 impl Default for FlatpakBackend {
@@ -27,13 +65,9 @@ impl Backend for FlatpakBackend {
         command_exists("flatpak")
     }
     
-    fn search(&self, query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>> {
-        let output = Command::new("flatpak")
-            .args(["search", query])
-            .output()?;
-            
+    fn search(&self, query: &str) -> Result<Vec<PackageResult>, OneInstallError> {
+        let stdout = run_command_output("flatpak", &["search", query]).unwrap_or_default();
         let mut results = Vec::new();
-        let stdout = String::from_utf8_lossy(&output.stdout);
         
         for line in stdout.lines() {
             let parts: Vec<&str> = line.split('\t').collect();
@@ -62,45 +96,57 @@ impl Backend for FlatpakBackend {
         Ok(results)
     }
     
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: flatpak install -y flathub {}", package);
-        
-        let status = Command::new("flatpak")
-            .args(["install", "-y", "flathub", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("flatpak install failed with exit code: {:?}", status.code()).into())
-        }
+    fn install(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("flatpak").args(["install", "-y", "flathub", package]))
     }
 
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: flatpak update -y {}", package);
-        
-        let status = Command::new("flatpak")
-            .args(["update", "-y", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("flatpak update failed with exit code: {:?}", status.code()).into())
-        }
+    fn install_many(&self, packages: &[&str]) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("flatpak").args(["install", "-y", "flathub"]).args(packages.iter().copied()))
     }
 
-    fn uninstall(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: flatpak uninstall -y {}", package);
+    fn update(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("flatpak").args(["update", "-y", package]))
+    }
+
+    fn uninstall(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("flatpak").args(["uninstall", "-y", package]))
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, OneInstallError> {
+        let output = run_command_output("flatpak", &["list", "--app", "--columns=application,version"])?;
+        Ok(self.parse_installed_output(&output))
+    }
+
+    fn outdated(&self) -> Result<Vec<OutdatedPackage>, OneInstallError> {
+        let output = run_command_output("flatpak", &["remote-ls", "--updates", "--columns=application,version"])?;
+        Ok(self.parse_installed_output(&output)
+            .into_iter()
+            .map(|p| {
+                let package = OutdatedPackage::new(p.name, "flatpak");
+                match p.version {
+                    Some(latest) => package.with_latest(latest),
+                    None => package,
+                }
+            })
+            .collect())
+    }
+
+    fn upgrade_all(&self) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("flatpak").args(["update", "-y"]))
+    }
+
+    fn info(&self, package: &str) -> Result<Option<PackageInfo>, OneInstallError> {
+        let output = match run_command_output("flatpak", &["remote-info", "flathub", package]) {
+            Ok(output) => output,
+            Err(_) => return Ok(None),
+        };
+        let mut info = self.parse_info_output(&output, package);
         
-        let status = Command::new("flatpak")
-            .args(["uninstall", "-y", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("flatpak uninstall failed with exit code: {:?}", status.code()).into())
+        // `flatpak info` only succeeds for installed refs
+        if let Ok(local) = run_command_output("flatpak", &["info", package]) {
+            info.installed_version = parse_fields(&local).get("Version").cloned();
         }
+        
+        Ok(Some(info))
     }
 }
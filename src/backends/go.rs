@@ -1,8 +1,12 @@
 //! Go backend for Go tools
 
-use super::{Backend, command_exists};
+use super::{
+    Backend, BackendCommand, command_exists, run_command, run_command_output, range_unsupported, executables_in_dir,
+    VersionReq,
+};
 use crate::search::PackageResult;
-use std::process::Command;
+use std::path::PathBuf;
+use crate::error::OneInstallError;
 
 pub struct GoBackend;
 
@@ -10,6 +14,34 @@ impl GoBackend {
     pub fn new() -> Self {
         Self
     }
+    
+    /// Directory `go install` places binaries in
+    fn bin_dir(&self) -> Option<PathBuf> {
+        let gobin = run_command_output("go", &["env", "GOBIN"]).ok()?;
+        let gobin = gobin.trim();
+        if !gobin.is_empty() {
+            return Some(PathBuf::from(gobin));
+        }
+        let gopath = run_command_output("go", &["env", "GOPATH"]).ok()?;
+        // GOPATH may be a list; go install uses the first entry
+        let first = std::env::split_paths(gopath.trim()).next()?;
+        Some(first.join("bin"))
+    }
+    
+    /// Whether `go version -m <binary>` output shows it was built from a package or module
+    fn built_from(output: &str, package: &str) -> bool {
+        output.lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .any(|parts| matches!(parts.as_slice(), ["path" | "mod", path, ..] if *path == package))
+    }
+    
+    /// Parse `go version -m <binary>` output into (module path, version)
+    fn parse_module_info(output: &str) -> Option<(String, String)> {
+        output.lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>())
+            .find(|parts| parts.first() == Some(&"mod") && parts.len() >= 3)
+            .map(|parts| (parts[1].to_string(), parts[2].to_string()))
+    }
 }
 
 impl Default for GoBackend {
@@ -27,38 +59,85 @@ impl Backend for GoBackend {
         command_exists("go")
     }
     
-    fn search(&self, _query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>> {
+    fn search(&self, _query: &str) -> Result<Vec<PackageResult>, OneInstallError> {
         // Go search is not built into the CLI in a way that returns package info easily.
         // We return empty for now as go install usually requires the full path anyway.
         Ok(Vec::new())
     }
     
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fn install(&self, package: &str) -> Result<(), OneInstallError> {
         let pkg_path = if package.contains('@') {
             package.to_string()
         } else {
             format!("{}@latest", package)
         };
         
-        println!("   Running: go install {}", pkg_path);
-        
-        let status = Command::new("go")
-            .args(["install", &pkg_path])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("go install failed with exit code: {:?}", status.code()).into())
+        run_command(&BackendCommand::new("go").args(["install", &pkg_path]))
+    }
+
+    fn install_version(&self, package: &str, version: &str) -> Result<(), OneInstallError> {
+        let module = package.split('@').next().unwrap_or(package);
+        self.install(&format!("{}@{}", module, version))
+    }
+
+    fn install_matching(&self, package: &str, version: &VersionReq) -> Result<(), OneInstallError> {
+        // Module queries: `@v1.2` is the newest v1.2.x, `@<v1.3` a single bound
+        let with_v = |v: &str| if v.starts_with('v') { v.to_string() } else { format!("v{}", v) };
+        match version {
+            VersionReq::Exact(version) => self.install_version(package, &with_v(version)),
+            VersionReq::Prefix(prefix) => self.install_version(package, &with_v(prefix)),
+            VersionReq::Range(range) if !range.contains([',', ' ', '^', '~']) => {
+                let bound = range.trim_start_matches(['<', '>', '=']);
+                let op = &range[..range.len() - bound.len()];
+                self.install_version(package, &format!("{}{}", op, with_v(bound)))
+            }
+            range => Err(range_unsupported(self.name(), range)),
         }
     }
 
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fn update(&self, package: &str) -> Result<(), OneInstallError> {
         // re-installing with @latest updates it
         self.install(package)
     }
 
-    fn uninstall(&self, _package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        Err("Go does not support a native 'uninstall' command. Please manually remove the binary from your $GOPATH/bin.".into())
+    fn uninstall(&self, _package: &str) -> Result<(), OneInstallError> {
+        Err(OneInstallError::Unsupported(
+            "Go does not support a native 'uninstall' command. Please manually remove the binary from your $GOPATH/bin.".to_string()
+        ))
+    }
+
+    fn binaries(&self, package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        let package = package.split('@').next().unwrap_or(package);
+        let Some(dir) = self.bin_dir() else {
+            return Ok(Vec::new());
+        };
+        Ok(executables_in_dir(&dir).into_iter()
+            .filter(|path| {
+                run_command_output("go", &["version", "-m", &path.to_string_lossy()])
+                    .is_ok_and(|info| Self::built_from(&info, package))
+            })
+            .collect())
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, OneInstallError> {
+        let dir = match self.bin_dir() {
+            Some(dir) if dir.is_dir() => dir,
+            _ => return Ok(Vec::new()),
+        };
+        
+        let mut results = Vec::new();
+        for entry in std::fs::read_dir(&dir)?.flatten() {
+            let path = entry.path();
+            let path_str = path.to_string_lossy();
+            let name = entry.file_name().to_string_lossy().to_string();
+            let mut result = PackageResult::new(name, "go".to_string());
+            if let Ok(info) = run_command_output("go", &["version", "-m", &path_str]) {
+                if let Some((module, version)) = Self::parse_module_info(&info) {
+                    result = result.with_version(version).with_description(module);
+                }
+            }
+            results.push(result);
+        }
+        Ok(results)
     }
 }
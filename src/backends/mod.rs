@@ -12,6 +12,9 @@ mod snap;
 mod flatpak;
 mod cargo;
 mod go;
+mod resolver;
+mod runner;
+mod spec;
 
 pub use winget::WingetBackend;
 pub use apt::AptBackend;
@@ -25,11 +28,97 @@ pub use snap::SnapBackend;
 pub use flatpak::FlatpakBackend;
 pub use cargo::CargoBackend;
 pub use go::GoBackend;
+pub use runner::{
+    BackendCommand, CommandRunner, SystemRunner, DryRunRunner,
+    set_runner, runner, enable_dry_run, is_dry_run, with_timeout, capture_commands, prime_sudo,
+};
+pub use resolver::BackendResolver;
+pub use spec::{backend_matches, canonical_backend_name, is_backend_name, BackendKind, PackageSpec, VersionReq, BACKEND_NAMES};
+use crate::error::OneInstallError;
+pub(crate) use runner::run_command;
 
 use crate::context::{OsContext, OsType, LinuxDistro};
 use crate::search::PackageResult;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 
+/// A package with a newer version available
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OutdatedPackage {
+    /// Package name
+    pub name: String,
+    /// Currently installed version (if known)
+    pub current: Option<String>,
+    /// Newest available version (if known)
+    pub latest: Option<String>,
+    /// Backend that manages the package
+    pub source: String,
+}
+
+impl OutdatedPackage {
+    /// Create a new outdated package entry
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            current: None,
+            latest: None,
+            source: source.into(),
+        }
+    }
+
+    /// Set the installed version
+    pub fn with_current(mut self, version: impl Into<String>) -> Self {
+        self.current = Some(version.into());
+        self
+    }
+
+    /// Set the newest available version
+    pub fn with_latest(mut self, version: impl Into<String>) -> Self {
+        self.latest = Some(version.into());
+        self
+    }
+}
+
+/// Detailed, normalized metadata about a single package
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PackageInfo {
+    /// Package name as the backend knows it
+    pub name: String,
+    /// Backend that provides the package
+    pub source: String,
+    /// Short description
+    pub description: Option<String>,
+    /// Version currently installed (None if not installed)
+    pub installed_version: Option<String>,
+    /// Version that would be installed
+    pub candidate_version: Option<String>,
+    /// Project homepage
+    pub homepage: Option<String>,
+    /// License identifier or name
+    pub license: Option<String>,
+    /// Download or installed size, as reported by the backend
+    pub size: Option<String>,
+    /// Direct dependencies
+    pub dependencies: Vec<String>,
+    /// Maintainer or publisher
+    pub maintainer: Option<String>,
+    /// Executables the package provides
+    pub binaries: Vec<String>,
+}
+
+impl PackageInfo {
+    /// Create an empty info record for a package
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            source: source.into(),
+            ..Default::default()
+        }
+    }
+}
+
 /// Trait for package manager backends
 pub trait Backend: Send + Sync {
     /// Get the name of this backend
@@ -38,21 +127,120 @@ pub trait Backend: Send + Sync {
     /// Check if this backend is available on the system
     fn is_available(&self) -> bool;
     
+    /// Whether changes run through `sudo`, which may prompt for a password
+    fn needs_root(&self) -> bool {
+        false
+    }
+    
     /// Search for packages matching the query
-    fn search(&self, query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>>;
+    fn search(&self, query: &str) -> Result<Vec<PackageResult>, OneInstallError>;
     
     /// Install a package
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>>;
+    fn install(&self, package: &str) -> Result<(), OneInstallError>;
 
     /// Update a package to the latest version
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>>;
+    fn update(&self, package: &str) -> Result<(), OneInstallError>;
 
     /// Uninstall a package
-    fn uninstall(&self, package: &str) -> Result<(), Box<dyn std::error::Error>>;
+    fn uninstall(&self, package: &str) -> Result<(), OneInstallError>;
+
+    /// Install several packages, in one native invocation where possible
+    ///
+    /// The default installs them one after another and stops at the first
+    /// failure; backends whose CLI accepts several packages override this.
+    fn install_many(&self, packages: &[&str]) -> Result<(), OneInstallError> {
+        packages.iter().try_for_each(|package| self.install(package))
+    }
+
+    /// Install an exact version of a package
+    ///
+    /// Backends without a native way to pin a version return
+    /// [`OneInstallError::Unsupported`].
+    fn install_version(&self, package: &str, version: &str) -> Result<(), OneInstallError> {
+        let _ = package;
+        Err(OneInstallError::Unsupported(format!(
+            "{} cannot install a specific version ({})", self.name(), version
+        )))
+    }
+
+    /// Install the newest version satisfying a requirement
+    ///
+    /// Exact versions go through [`Backend::install_version`]; backends
+    /// override this to translate prefixes and ranges into native syntax.
+    fn install_matching(&self, package: &str, version: &VersionReq) -> Result<(), OneInstallError> {
+        match version {
+            VersionReq::Exact(version) => self.install_version(package, version),
+            other => Err(range_unsupported(self.name(), other)),
+        }
+    }
+
+    /// List packages installed through this backend
+    ///
+    /// Backends that cannot enumerate their installs return an empty list.
+    fn list_installed(&self) -> Result<Vec<PackageResult>, OneInstallError> {
+        Ok(Vec::new())
+    }
+
+    /// List installed packages that have a newer version available
+    ///
+    /// Backends that cannot check for updates return an empty list.
+    fn outdated(&self) -> Result<Vec<OutdatedPackage>, OneInstallError> {
+        Ok(Vec::new())
+    }
+
+    /// Look up detailed metadata for a package
+    ///
+    /// Returns `Ok(None)` when the backend does not know the package or
+    /// cannot provide metadata.
+    fn info(&self, _package: &str) -> Result<Option<PackageInfo>, OneInstallError> {
+        Ok(None)
+    }
+
+    /// Files a package installed, as the backend reports them
+    ///
+    /// Backends that cannot list a package's files return an empty list.
+    fn installed_files(&self, _package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        Ok(Vec::new())
+    }
+
+    /// Executables a package installed
+    ///
+    /// The default keeps the executable files in a `bin` or `sbin`
+    /// directory from [`Backend::installed_files`]; backends that know their
+    /// bin directory better override this.
+    fn binaries(&self, package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        Ok(executables_in_bin(self.installed_files(package)?))
+    }
+
+    /// Upgrade every outdated package managed by this backend
+    ///
+    /// Backends that cannot check for updates return
+    /// [`OneInstallError::Unsupported`]; ones that can but lack a native
+    /// "upgrade everything" command use [`upgrade_outdated`].
+    fn upgrade_all(&self) -> Result<(), OneInstallError> {
+        Err(OneInstallError::Unsupported(format!(
+            "{} cannot check for updates; update its packages individually", self.name()
+        )))
+    }
+}
+
+/// Upgrade each entry from `outdated()` individually, continuing past failures
+pub(crate) fn upgrade_outdated(backend: &dyn Backend) -> Result<(), OneInstallError> {
+    let outdated = backend.outdated()?;
+    let failed: Vec<String> = outdated.iter()
+        .filter(|p| backend.update(&p.name).is_err())
+        .map(|p| p.name.clone())
+        .collect();
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        Err(OneInstallError::PartialFailure(format!("Failed to upgrade: {}", failed.join(", "))))
+    }
 }
 
 /// Get the appropriate backend for the detected OS context
-pub fn get_backend_for_context(context: &OsContext) -> Result<Box<dyn Backend>, Box<dyn std::error::Error>> {
+pub fn get_backend_for_context(context: &OsContext) -> Result<Box<dyn Backend>, OneInstallError> {
     match &context.os_type {
         OsType::Windows => {
             Ok(Box::new(WingetBackend::new()))
@@ -67,7 +255,7 @@ pub fn get_backend_for_context(context: &OsContext) -> Result<Box<dyn Backend>,
                     if apt.is_available() {
                         Ok(Box::new(apt))
                     } else {
-                        Err("Could not detect a supported package manager".into())
+                        Err(OneInstallError::NoSupportedBackend("Could not detect a supported package manager".to_string()))
                     }
                 }
             }
@@ -76,7 +264,7 @@ pub fn get_backend_for_context(context: &OsContext) -> Result<Box<dyn Backend>,
             Ok(Box::new(BrewBackend::new()))
         }
         OsType::Unknown => {
-            Err("Unknown operating system".into())
+            Err(OneInstallError::NoSupportedBackend("Unknown operating system".to_string()))
         }
     }
 }
@@ -104,6 +292,62 @@ pub fn get_all_available_backends() -> Vec<Box<dyn Backend>> {
         .collect()
 }
 
+/// Error for a backend that can only pin exact versions
+pub(crate) fn range_unsupported(backend: &str, version: &VersionReq) -> OneInstallError {
+    OneInstallError::Unsupported(format!(
+        "{} cannot resolve the version requirement {}; pin an exact version instead", backend, version
+    ))
+}
+
+/// Parse a file list with one absolute path per line (`dpkg -L`, `rpm -ql`)
+///
+/// Anything else on a line of its own, like rpm's "(contains no files)",
+/// is skipped.
+pub(crate) fn parse_path_lines(output: &str) -> Vec<PathBuf> {
+    output.lines()
+        .map(str::trim)
+        .filter(|line| line.starts_with('/'))
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// Keep the executable files that live directly in a `bin` or `sbin` directory
+pub(crate) fn executables_in_bin(files: Vec<PathBuf>) -> Vec<PathBuf> {
+    files.into_iter()
+        .filter(|path| {
+            path.parent()
+                .and_then(Path::file_name)
+                .is_some_and(|dir| dir == "bin" || dir == "sbin")
+        })
+        .filter(|path| is_executable(path))
+        .collect()
+}
+
+/// Executable files directly inside a directory, sorted by name
+pub(crate) fn executables_in_dir(dir: &Path) -> Vec<PathBuf> {
+    let mut found: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| is_executable(p)).collect())
+        .unwrap_or_default();
+    found.sort();
+    found
+}
+
+/// Whether a path is a file (following symlinks) that can be executed
+pub(crate) fn is_executable(path: &Path) -> bool {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return false;
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+    }
+    #[cfg(not(unix))]
+    {
+        metadata.is_file()
+    }
+}
+
 /// Helper to check if a command exists
 pub(crate) fn command_exists(cmd: &str) -> bool {
     #[cfg(target_os = "windows")]
@@ -130,15 +374,76 @@ pub(crate) fn command_exists(cmd: &str) -> bool {
 }
 
 /// Helper to run a command and capture output
-pub(crate) fn run_command_output(cmd: &str, args: &[&str]) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    let output = Command::new(cmd)
-        .args(args)
-        .output()?;
-    
-    if output.status.success() {
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+pub(crate) fn run_command_output(cmd: &str, args: &[&str]) -> Result<String, OneInstallError> {
+    runner().output(&BackendCommand::new(cmd).args(args.iter().copied()))
+}
+
+/// Helper to run a query whose exit code signals results rather than failure
+pub(crate) fn run_query_lenient(cmd: &str, args: &[&str]) -> Result<String, OneInstallError> {
+    runner().output(&BackendCommand::new(cmd).args(args.iter().copied()).allow_nonzero_exit())
+}
+
+/// Parse "Key: value" lines into a map (first occurrence of each key wins)
+pub(crate) fn parse_fields(output: &str) -> HashMap<String, String> {
+    let mut fields = HashMap::new();
+    for line in output.lines() {
+        if let Some((key, value)) = line.split_once(':') {
+            let key = key.trim();
+            if !key.is_empty() {
+                fields.entry(key.to_string()).or_insert_with(|| value.trim().to_string());
+            }
+        }
+    }
+    fields
+}
+
+/// Read a string field from JSON via a pointer like `/versions/stable`
+pub(crate) fn json_str(value: &serde_json::Value, pointer: &str) -> Option<String> {
+    value.pointer(pointer)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(String::from)
+}
+
+/// Format a byte count for display
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "kB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1000.0 && unit < UNITS.len() - 1 {
+        size /= 1000.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
     } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        Err(format!("Command failed: {}", stderr).into())
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_fields() {
+        let fields = parse_fields("Name    : jq\nVersion : 1.7\n  Installed: (none)\nVersion : 2.0\n");
+        assert_eq!(fields.get("Name").map(String::as_str), Some("jq"));
+        assert_eq!(fields.get("Version").map(String::as_str), Some("1.7"));
+        assert_eq!(fields.get("Installed").map(String::as_str), Some("(none)"));
+    }
+
+    #[test]
+    fn test_parse_path_lines() {
+        let files = parse_path_lines("/.\n/usr/bin\n/usr/bin/jq\n/usr/share/doc/jq/copyright\n");
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[2], PathBuf::from("/usr/bin/jq"));
+        assert!(parse_path_lines("(contains no files)\n").is_empty());
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1_500_000), "1.5 MB");
     }
 }
@@ -1,8 +1,12 @@
 //! NPM backend for Node.js packages
 
-use super::{Backend, command_exists, run_command_output};
+use super::{
+    Backend, BackendCommand, OutdatedPackage, PackageInfo,
+    command_exists, run_command, run_command_output, run_query_lenient, format_bytes, json_str, VersionReq,
+};
+use std::path::{Path, PathBuf};
 use crate::search::PackageResult;
-use std::process::Command;
+use crate::error::OneInstallError;
 
 /// NPM package manager backend
 pub struct NpmBackend;
@@ -12,6 +16,12 @@ impl NpmBackend {
         Self
     }
     
+    /// Executable names from `npm ls -g --json --long` (`bin` is a map or one path)
+    fn parse_bin_names(&self, output: &str, package: &str) -> Vec<String> {
+        let json: serde_json::Value = serde_json::from_str(output).unwrap_or_default();
+        bin_names(json.get("dependencies").and_then(|deps| deps.get(package)).and_then(|dep| dep.get("bin")), package)
+    }
+    
     /// Parse npm search output into PackageResults
     fn parse_search_output(&self, output: &str) -> Vec<PackageResult> {
         // npm search --json returns JSON array
@@ -40,6 +50,103 @@ impl NpmBackend {
             })
             .collect()
     }
+    
+    /// Parse `npm ls -g --depth=0 --json` output into PackageResults
+    fn parse_installed_output(&self, output: &str) -> Vec<PackageResult> {
+        let json: serde_json::Value = match serde_json::from_str(output) {
+            Ok(v) => v,
+            Err(_) => return Vec::new(),
+        };
+        
+        json.get("dependencies")
+            .and_then(|d| d.as_object())
+            .map(|deps| {
+                deps.iter()
+                    .map(|(name, info)| {
+                        let mut result = PackageResult::new(name.clone(), "npm".to_string());
+                        if let Some(v) = info.get("version").and_then(|v| v.as_str()) {
+                            result = result.with_version(v);
+                        }
+                        result
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    
+    /// Parse `npm outdated -g --json` output
+    fn parse_outdated_output(&self, output: &str) -> Result<Vec<OutdatedPackage>, OneInstallError> {
+        if output.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        let json: serde_json::Value = serde_json::from_str(output)?;
+        
+        // npm reports registry/network failures as {"error": {"summary": ...}}
+        if let Some(error) = json.get("error") {
+            let summary = error.get("summary").and_then(|v| v.as_str()).unwrap_or("unknown error");
+            return Err(OneInstallError::CommandFailed {
+                command: "npm outdated".to_string(),
+                code: None,
+                stderr: summary.to_string(),
+            });
+        }
+        
+        Ok(json.as_object()
+            .map(|packages| {
+                packages.iter()
+                    .map(|(name, info)| {
+                        let mut package = OutdatedPackage::new(name.as_str(), "npm");
+                        if let Some(current) = info.get("current").and_then(|v| v.as_str()) {
+                            package = package.with_current(current);
+                        }
+                        if let Some(latest) = info.get("latest").and_then(|v| v.as_str()) {
+                            package = package.with_latest(latest);
+                        }
+                        package
+                    })
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+    
+    /// Parse `npm view <pkg> --json` output into a PackageInfo
+    fn parse_view_output(&self, output: &str) -> Option<PackageInfo> {
+        let json: serde_json::Value = serde_json::from_str(output).ok()?;
+        if json.get("error").is_some() {
+            return None;
+        }
+        
+        let mut info = PackageInfo::new(json_str(&json, "/name")?, "npm");
+        info.candidate_version = json_str(&json, "/version");
+        info.description = json_str(&json, "/description");
+        info.homepage = json_str(&json, "/homepage");
+        info.license = json_str(&json, "/license");
+        info.size = json.pointer("/dist/unpackedSize")
+            .and_then(|v| v.as_u64())
+            .map(format_bytes);
+        info.dependencies = json.get("dependencies")
+            .and_then(|v| v.as_object())
+            .map(|deps| deps.keys().cloned().collect())
+            .unwrap_or_default();
+        // Maintainers are either "name <email>" strings or {name, email} objects
+        info.maintainer = json.pointer("/maintainers/0").and_then(|m| {
+            m.as_str().map(String::from).or_else(|| json_str(m, "/name"))
+        });
+        info.binaries = bin_names(json.get("bin"), &info.name);
+        Some(info)
+    }
+}
+
+/// Command names from a package.json `bin` field
+///
+/// `bin` is either a map of command names or a single path, which npm
+/// names after the package minus any scope (`@scope/tool` → `tool`).
+fn bin_names(bin: Option<&serde_json::Value>, package: &str) -> Vec<String> {
+    match bin {
+        Some(serde_json::Value::Object(bins)) => bins.keys().cloned().collect(),
+        Some(serde_json::Value::String(_)) => vec![package.rsplit('/').next().unwrap_or(package).to_string()],
+        _ => Vec::new(),
+    }
 }
 
 impl Default for NpmBackend {
@@ -57,52 +164,92 @@ impl Backend for NpmBackend {
         command_exists("npm")
     }
     
-    fn search(&self, query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>> {
+    fn search(&self, query: &str) -> Result<Vec<PackageResult>, OneInstallError> {
         // Use npm search with limited results for speed
         let output = run_command_output("npm", &["search", query, "--long", "--parseable"])?;
         Ok(self.parse_search_output(&output))
     }
     
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: npm install -g {}", package);
-        
-        let status = Command::new("npm")
-            .args(["install", "-g", package])
-            .status()?;
-        
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("npm install failed with exit code: {:?}", status.code()).into())
-        }
+    fn install(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("npm").args(["install", "-g", package]))
     }
 
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: npm update -g {}", package);
-        
-        let status = Command::new("npm")
-            .args(["update", "-g", package])
-            .status()?;
-        
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("npm update failed with exit code: {:?}", status.code()).into())
-        }
+    fn install_many(&self, packages: &[&str]) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("npm").args(["install", "-g"]).args(packages.iter().copied()))
+    }
+
+    fn install_version(&self, package: &str, version: &str) -> Result<(), OneInstallError> {
+        let pinned = format!("{}@{}", package, version);
+        run_command(&BackendCommand::new("npm").args(["install", "-g", &pinned]))
+    }
+
+    fn install_matching(&self, package: &str, version: &VersionReq) -> Result<(), OneInstallError> {
+        // npm resolves semver ranges itself; comparators are space-separated
+        let range = match version {
+            VersionReq::Exact(version) => return self.install_version(package, version),
+            VersionReq::Prefix(prefix) => format!("{}.x", prefix),
+            VersionReq::Range(range) => range.replace(',', " "),
+        };
+        self.install_version(package, &range)
+    }
+
+    fn update(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("npm").args(["update", "-g", package]))
+    }
+
+    fn uninstall(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("npm").args(["uninstall", "-g", package]))
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, OneInstallError> {
+        let output = run_command_output("npm", &["ls", "-g", "--depth=0", "--json"])?;
+        Ok(self.parse_installed_output(&output))
+    }
+
+    fn outdated(&self) -> Result<Vec<OutdatedPackage>, OneInstallError> {
+        // npm outdated exits 1 when anything is outdated
+        let output = run_query_lenient("npm", &["outdated", "-g", "--json"])?;
+        self.parse_outdated_output(&output)
     }
 
-    fn uninstall(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: npm uninstall -g {}", package);
+    fn upgrade_all(&self) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("npm").args(["update", "-g"]))
+    }
+
+    fn binaries(&self, package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        let output = run_query_lenient("npm", &["ls", "-g", "--json", "--long", "--depth=0", package])?;
+        let names = self.parse_bin_names(&output, package);
+        if names.is_empty() {
+            return Ok(Vec::new());
+        }
         
-        let status = Command::new("npm")
-            .args(["uninstall", "-g", package])
-            .status()?;
+        // Global bins live in <prefix>/bin, or the prefix itself (as .cmd) on Windows
+        let prefix = run_command_output("npm", &["prefix", "-g"])?;
+        let prefix = Path::new(prefix.trim());
+        Ok(names.into_iter()
+            .map(|name| if cfg!(windows) { prefix.join(format!("{}.cmd", name)) } else { prefix.join("bin").join(name) })
+            .filter(|path| path.exists())
+            .collect())
+    }
+
+    fn info(&self, package: &str) -> Result<Option<PackageInfo>, OneInstallError> {
+        let mut info = match run_command_output("npm", &["view", package, "--json"]) {
+            Ok(output) => match self.parse_view_output(&output) {
+                Some(info) => info,
+                None => return Ok(None),
+            },
+            Err(_) => return Ok(None),
+        };
         
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("npm uninstall failed with exit code: {:?}", status.code()).into())
+        // npm ls exits 1 when the package is not installed
+        if let Ok(output) = run_query_lenient("npm", &["ls", "-g", package, "--depth=0", "--json"]) {
+            info.installed_version = self.parse_installed_output(&output)
+                .into_iter()
+                .find(|p| p.name == info.name)
+                .and_then(|p| p.version);
         }
+        
+        Ok(Some(info))
     }
 }
 
@@ -115,4 +262,57 @@ mod tests {
         let backend = NpmBackend::new();
         assert_eq!(backend.name(), "npm");
     }
+    
+    #[test]
+    fn test_parse_installed_output() {
+        let backend = NpmBackend::new();
+        let output = r#"{"name":"lib","dependencies":{"prettier":{"version":"3.1.0"}}}"#;
+        let results = backend.parse_installed_output(output);
+        
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "prettier");
+        assert_eq!(results[0].version.as_deref(), Some("3.1.0"));
+    }
+    
+    #[test]
+    fn test_parse_bin_names() {
+        let backend = NpmBackend::new();
+        let output = r#"{"dependencies":{"typescript":{"version":"5.3.3","bin":{"tsc":"bin/tsc","tsserver":"bin/tsserver"}}}}"#;
+        assert_eq!(backend.parse_bin_names(output, "typescript"), vec!["tsc", "tsserver"]);
+        
+        let output = r#"{"dependencies":{"@scope/tool":{"version":"1.0.0","bin":"cli.js"}}}"#;
+        assert_eq!(backend.parse_bin_names(output, "@scope/tool"), vec!["tool"]);
+        assert!(backend.parse_bin_names("{}", "typescript").is_empty());
+    }
+    
+    #[test]
+    fn test_parse_outdated_output() {
+        let backend = NpmBackend::new();
+        let output = r#"{"prettier":{"current":"3.0.0","wanted":"3.1.0","latest":"3.1.0"}}"#;
+        let results = backend.parse_outdated_output(output).unwrap();
+        
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].name, "prettier");
+        assert_eq!(results[0].latest.as_deref(), Some("3.1.0"));
+        
+        let error = r#"{"error":{"code":"ENOTFOUND","summary":"request failed"}}"#;
+        assert!(backend.parse_outdated_output(error).is_err());
+    }
+    
+    #[test]
+    fn test_parse_view_output() {
+        let backend = NpmBackend::new();
+        let output = r#"{"name":"prettier","version":"3.1.0","license":"MIT",
+            "maintainers":["fisker <lionkay@gmail.com>"],"bin":{"prettier":"bin/prettier.cjs"},
+            "dist":{"unpackedSize":8400000}}"#;
+        let info = backend.parse_view_output(output).unwrap();
+        
+        assert_eq!(info.candidate_version.as_deref(), Some("3.1.0"));
+        assert_eq!(info.binaries, vec!["prettier"]);
+        assert_eq!(info.size.as_deref(), Some("8.4 MB"));
+        assert_eq!(info.maintainer.as_deref(), Some("fisker <lionkay@gmail.com>"));
+        
+        let scoped = r#"{"name":"@scope/tool","version":"1.0.0","bin":"cli.js"}"#;
+        assert_eq!(backend.parse_view_output(scoped).unwrap().binaries, vec!["tool"]);
+    }
 }
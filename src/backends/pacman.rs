@@ -1,8 +1,12 @@
 //! Pacman backend for Arch Linux
 
-use super::{Backend, command_exists};
+use super::{
+    Backend, BackendCommand, OutdatedPackage, PackageInfo,
+    command_exists, runner, run_command, run_command_output, parse_fields, parse_path_lines,
+};
 use crate::search::PackageResult;
-use std::process::Command;
+use crate::error::OneInstallError;
+use std::path::PathBuf;
 
 pub struct PacmanBackend;
 
@@ -10,6 +14,34 @@ impl PacmanBackend {
     pub fn new() -> Self {
         Self
     }
+    
+    /// Parse `pacman -Q` output ("name version")
+    fn parse_installed_output(&self, output: &str) -> Vec<PackageResult> {
+        output.lines()
+            .filter_map(|line| {
+                let (name, version) = line.split_once(' ')?;
+                Some(PackageResult::new(name.to_string(), "pacman".to_string()).with_version(version.trim()))
+            })
+            .collect()
+    }
+    
+    /// Parse `pacman -Qu` output ("name old -> new")
+    fn parse_outdated_output(&self, output: &str) -> Vec<OutdatedPackage> {
+        output.lines()
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() >= 4 && parts[2] == "->" {
+                    Some(
+                        OutdatedPackage::new(parts[0], "pacman")
+                            .with_current(parts[1])
+                            .with_latest(parts[3])
+                    )
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 impl Default for PacmanBackend {
@@ -27,13 +59,14 @@ impl Backend for PacmanBackend {
         command_exists("pacman")
     }
     
-    fn search(&self, query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>> {
-        let output = Command::new("pacman")
-            .args(["-Ss", query])
-            .output()?;
-            
+    fn needs_root(&self) -> bool {
+        true
+    }
+    
+    fn search(&self, query: &str) -> Result<Vec<PackageResult>, OneInstallError> {
+        // `pacman -Ss` exits non-zero when nothing matches
+        let stdout = run_command_output("pacman", &["-Ss", query]).unwrap_or_default();
         let mut results = Vec::new();
-        let stdout = String::from_utf8_lossy(&output.stdout);
         
         let mut current_pkg = None;
         
@@ -62,35 +95,67 @@ impl Backend for PacmanBackend {
         Ok(results)
     }
     
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: sudo pacman -S --noconfirm {}", package);
-        
-        let status = Command::new("sudo")
-            .args(["pacman", "-S", "--noconfirm", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("pacman install failed with exit code: {:?}", status.code()).into())
-        }
+    fn install(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("pacman").args(["-S", "--noconfirm", package]).sudo())
+    }
+
+    fn install_many(&self, packages: &[&str]) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("pacman").args(["-S", "--noconfirm"]).args(packages.iter().copied()).sudo())
     }
 
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fn update(&self, package: &str) -> Result<(), OneInstallError> {
         self.install(package)
     }
 
-    fn uninstall(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: sudo pacman -Rns --noconfirm {}", package);
+    fn uninstall(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("pacman").args(["-Rns", "--noconfirm", package]).sudo())
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, OneInstallError> {
+        let output = run_command_output("pacman", &["-Q"])?;
+        Ok(self.parse_installed_output(&output))
+    }
+
+    fn outdated(&self) -> Result<Vec<OutdatedPackage>, OneInstallError> {
+        // pacman -Qu exits 1 with no output when nothing is outdated
+        let output = runner().output(&BackendCommand::new("pacman").arg("-Qu").allow_silent_exit_code(1))?;
+        Ok(self.parse_outdated_output(&output))
+    }
+
+    fn upgrade_all(&self) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("pacman").args(["-Syu", "--noconfirm"]).sudo())
+    }
+
+    fn installed_files(&self, package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        let output = run_command_output("pacman", &["-Qlq", package])?;
+        Ok(parse_path_lines(&output))
+    }
+
+    fn info(&self, package: &str) -> Result<Option<PackageInfo>, OneInstallError> {
+        // Sync database first, then the local database for foreign (AUR) packages
+        let output = match run_command_output("pacman", &["-Si", package])
+            .or_else(|_| run_command_output("pacman", &["-Qi", package]))
+        {
+            Ok(output) => output,
+            Err(_) => return Ok(None),
+        };
+        let fields = parse_fields(&output);
+        let field = |key: &str| fields.get(key).filter(|v| !v.is_empty() && *v != "None").cloned();
         
-        let status = Command::new("sudo")
-            .args(["pacman", "-Rns", "--noconfirm", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("pacman uninstall failed with exit code: {:?}", status.code()).into())
-        }
+        let mut info = PackageInfo::new(field("Name").unwrap_or_else(|| package.to_string()), "pacman");
+        info.candidate_version = field("Version");
+        info.description = field("Description");
+        info.homepage = field("URL");
+        info.license = field("Licenses");
+        info.size = field("Installed Size");
+        info.maintainer = field("Packager");
+        info.dependencies = field("Depends On")
+            .map(|deps| deps.split_whitespace().map(String::from).collect())
+            .unwrap_or_default();
+        info.installed_version = run_command_output("pacman", &["-Q", package])
+            .ok()
+            .and_then(|out| out.split_whitespace().nth(1).map(String::from));
+        
+        Ok(Some(info))
     }
 }
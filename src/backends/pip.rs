@@ -1,8 +1,13 @@
 //! Pip/pipx backend for Python packages
 
-use super::{Backend, command_exists, run_command_output};
+use super::{
+    Backend, BackendCommand, OutdatedPackage, PackageInfo,
+    command_exists, run_command, run_command_output, parse_fields, range_unsupported, executables_in_bin,
+    VersionReq,
+};
 use crate::search::PackageResult;
-use std::process::Command;
+use crate::error::OneInstallError;
+use std::path::{Component, Path, PathBuf};
 
 /// Pip package manager backend (prefers pipx for global installs)
 pub struct PipBackend {
@@ -16,6 +21,15 @@ impl PipBackend {
         }
     }
     
+    /// Install a PEP 508 requirement (`name==1.2`, `name>=1,<2`)
+    fn install_requirement(&self, requirement: &str) -> Result<(), OneInstallError> {
+        if self.use_pipx {
+            run_command(&BackendCommand::new("pipx").args(["install", "--force", requirement]))
+        } else {
+            run_command(&BackendCommand::new(pip_command()).args(["install", "--user", requirement]))
+        }
+    }
+    
     /// Parse pip search output
     /// Note: pip search is disabled on PyPI, so we use pip index versions
     fn parse_search_output(&self, output: &str) -> Vec<PackageResult> {
@@ -40,6 +54,113 @@ impl PipBackend {
             })
             .collect()
     }
+    
+    /// Parse `pipx list --short` output ("name version")
+    fn parse_pipx_installed(&self, output: &str) -> Vec<PackageResult> {
+        output.lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let name = parts.next()?.to_string();
+                let mut result = PackageResult::new(name, "pipx".to_string());
+                if let Some(version) = parts.next() {
+                    result = result.with_version(version);
+                }
+                Some(result)
+            })
+            .collect()
+    }
+    
+    /// Parse `pip list --format=json` output
+    fn parse_pip_installed(&self, output: &str) -> Vec<PackageResult> {
+        let json: serde_json::Value = match serde_json::from_str(output) {
+            Ok(v) => v,
+            Err(_) => return Vec::new(),
+        };
+        
+        json.as_array()
+            .map(|items| {
+                items.iter()
+                    .filter_map(|item| {
+                        let name = item.get("name")?.as_str()?.to_string();
+                        let mut result = PackageResult::new(name, "pip".to_string());
+                        if let Some(v) = item.get("version").and_then(|v| v.as_str()) {
+                            result = result.with_version(v);
+                        }
+                        Some(result)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    
+    /// Parse `pip list --outdated --format=json` output
+    fn parse_pip_outdated(&self, output: &str) -> Vec<OutdatedPackage> {
+        let json: serde_json::Value = match serde_json::from_str(output) {
+            Ok(v) => v,
+            Err(_) => return Vec::new(),
+        };
+        
+        json.as_array()
+            .map(|items| {
+                items.iter()
+                    .filter_map(|item| {
+                        let name = item.get("name")?.as_str()?;
+                        let mut package = OutdatedPackage::new(name, "pip");
+                        if let Some(v) = item.get("version").and_then(|v| v.as_str()) {
+                            package = package.with_current(v);
+                        }
+                        if let Some(v) = item.get("latest_version").and_then(|v| v.as_str()) {
+                            package = package.with_latest(v);
+                        }
+                        Some(package)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+    
+    /// Parse `pip show` output into a PackageInfo
+    fn parse_show_output(&self, output: &str) -> Option<PackageInfo> {
+        let fields = parse_fields(output);
+        let field = |key: &str| fields.get(key).filter(|v| !v.is_empty() && *v != "UNKNOWN").cloned();
+        
+        let mut info = PackageInfo::new(field("Name")?, self.name());
+        info.installed_version = field("Version");
+        info.description = field("Summary");
+        info.homepage = field("Home-page");
+        info.license = field("License");
+        info.maintainer = field("Author").or_else(|| field("Author-email"));
+        info.dependencies = field("Requires")
+            .map(|deps| deps.split(',').map(|d| d.trim().to_string()).collect())
+            .unwrap_or_default();
+        Some(info)
+    }
+    
+    /// Absolute paths of the `Files:` section of `pip show -f` output
+    fn parse_show_files(&self, output: &str) -> Vec<PathBuf> {
+        let Some(location) = parse_fields(output).get("Location").map(PathBuf::from) else {
+            return Vec::new();
+        };
+        output.lines()
+            .skip_while(|line| !line.starts_with("Files:"))
+            .skip(1)
+            .take_while(|line| line.starts_with(char::is_whitespace))
+            .map(|line| normalize(&location.join(line.trim())))
+            .collect()
+    }
+    
+    /// App paths of one venv in `pipx list --json` output
+    fn parse_pipx_apps(&self, output: &str, package: &str) -> Vec<PathBuf> {
+        let json: serde_json::Value = serde_json::from_str(output).unwrap_or_default();
+        json.pointer(&format!("/venvs/{}/metadata/main_package/app_paths", package))
+            .and_then(|paths| paths.as_array())
+            .map(|paths| {
+                paths.iter()
+                    .filter_map(|path| path.get("__Path__")?.as_str().map(PathBuf::from))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
 
 impl Default for PipBackend {
@@ -57,12 +178,11 @@ impl Backend for PipBackend {
         command_exists("pip") || command_exists("pip3") || command_exists("pipx")
     }
     
-    fn search(&self, query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>> {
+    fn search(&self, query: &str) -> Result<Vec<PackageResult>, OneInstallError> {
         // pip search is deprecated, try pip index versions for exact package
-        let pip_cmd = if command_exists("pip3") { "pip3" } else { "pip" };
-        
-        match run_command_output(pip_cmd, &["index", "versions", query]) {
+        match run_command_output(pip_command(), &["index", "versions", query]) {
             Ok(output) => Ok(self.parse_search_output(&output)),
+            Err(e @ OneInstallError::TimedOut { .. }) => Err(e),
             Err(_) => {
                 // Fallback: return a single result for the query (assume it exists)
                 Ok(vec![PackageResult::new(query.to_string(), "pip".to_string())])
@@ -70,64 +190,165 @@ impl Backend for PipBackend {
         }
     }
     
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fn install(&self, package: &str) -> Result<(), OneInstallError> {
         if self.use_pipx {
-            println!("   Running: pipx install {}", package);
-            
-            let status = Command::new("pipx")
-                .args(["install", package])
-                .status()?;
-            
-            if status.success() {
-                Ok(())
-            } else {
-                Err(format!("pipx install failed with exit code: {:?}", status.code()).into())
-            }
+            run_command(&BackendCommand::new("pipx").args(["install", package]))
+        } else {
+            run_command(&BackendCommand::new(pip_command()).args(["install", "--user", package]))
+        }
+    }
+
+    fn install_many(&self, packages: &[&str]) -> Result<(), OneInstallError> {
+        if self.use_pipx {
+            run_command(&BackendCommand::new("pipx").arg("install").args(packages.iter().copied()))
         } else {
-            let pip_cmd = if command_exists("pip3") { "pip3" } else { "pip" };
-            println!("   Running: {} install --user {}", pip_cmd, package);
-            
-            let status = Command::new(pip_cmd)
-                .args(["install", "--user", package])
-                .status()?;
-            
-            if status.success() {
-                Ok(())
-            } else {
-                Err(format!("pip install failed with exit code: {:?}", status.code()).into())
+            run_command(&BackendCommand::new(pip_command()).args(["install", "--user"]).args(packages.iter().copied()))
+        }
+    }
+
+    fn install_version(&self, package: &str, version: &str) -> Result<(), OneInstallError> {
+        self.install_requirement(&format!("{}=={}", package, version))
+    }
+
+    fn install_matching(&self, package: &str, version: &VersionReq) -> Result<(), OneInstallError> {
+        match version {
+            VersionReq::Exact(version) => self.install_version(package, version),
+            VersionReq::Prefix(prefix) => self.install_requirement(&format!("{}=={}.*", package, prefix)),
+            // PEP 440 specifiers (`>=1,<2`, `~=1.4`, `!=1.5`) pass through as-is
+            VersionReq::Range(range) if range.starts_with(['>', '<', '!', '=']) || range.starts_with("~=") => {
+                self.install_requirement(&format!("{}{}", package, range))
             }
+            range => Err(range_unsupported(self.name(), range)),
+        }
+    }
+
+    fn update(&self, package: &str) -> Result<(), OneInstallError> {
+        if self.use_pipx {
+            run_command(&BackendCommand::new("pipx").args(["upgrade", package]))
+        } else {
+            run_command(&BackendCommand::new(pip_command()).args(["install", "--user", "--upgrade", package]))
+        }
+    }
+
+    fn uninstall(&self, package: &str) -> Result<(), OneInstallError> {
+        if self.use_pipx {
+            run_command(&BackendCommand::new("pipx").args(["uninstall", package]))
+        } else {
+            run_command(&BackendCommand::new(pip_command()).args(["uninstall", "-y", package]))
+        }
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, OneInstallError> {
+        if self.use_pipx {
+            let output = run_command_output("pipx", &["list", "--short"])?;
+            Ok(self.parse_pipx_installed(&output))
+        } else {
+            let output = run_command_output(pip_command(), &["list", "--format=json"])?;
+            Ok(self.parse_pip_installed(&output))
         }
     }
 
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fn outdated(&self) -> Result<Vec<OutdatedPackage>, OneInstallError> {
         if self.use_pipx {
-            println!("   Running: pipx upgrade {}", package);
-            let status = Command::new("pipx").args(["upgrade", package]).status()?;
-            if status.success() { Ok(()) } else { Err(format!("pipx upgrade failed: {:?}", status.code()).into()) }
+            // pipx has no outdated query; compare each venv against the index
+            let mut outdated = Vec::new();
+            for installed in self.list_installed()? {
+                let latest = run_command_output(pip_command(), &["index", "versions", &installed.name])
+                    .ok()
+                    .and_then(|out| self.parse_search_output(&out).into_iter().next())
+                    .and_then(|r| r.version);
+                if let (Some(current), Some(latest)) = (installed.version, latest) {
+                    if current != latest {
+                        outdated.push(
+                            OutdatedPackage::new(installed.name, "pipx")
+                                .with_current(current)
+                                .with_latest(latest)
+                        );
+                    }
+                }
+            }
+            Ok(outdated)
         } else {
-            let pip_cmd = if command_exists("pip3") { "pip3" } else { "pip" };
-            println!("   Running: {} install --user --upgrade {}", pip_cmd, package);
-            let status = Command::new(pip_cmd)
-                .args(["install", "--user", "--upgrade", package])
-                .status()?;
-            if status.success() { Ok(()) } else { Err(format!("pip upgrade failed: {:?}", status.code()).into()) }
+            let output = run_command_output(pip_command(), &["list", "--user", "--outdated", "--format=json"])?;
+            Ok(self.parse_pip_outdated(&output))
         }
     }
 
-    fn uninstall(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
+    fn upgrade_all(&self) -> Result<(), OneInstallError> {
         if self.use_pipx {
-            println!("   Running: pipx uninstall {}", package);
-            let status = Command::new("pipx").args(["uninstall", package]).status()?;
-            if status.success() { Ok(()) } else { Err(format!("pipx uninstall failed: {:?}", status.code()).into()) }
+            run_command(&BackendCommand::new("pipx").arg("upgrade-all"))
         } else {
-            let pip_cmd = if command_exists("pip3") { "pip3" } else { "pip" };
-            println!("   Running: {} uninstall -y {}", pip_cmd, package);
-            let status = Command::new(pip_cmd)
-                .args(["uninstall", "-y", package])
-                .status()?;
-            if status.success() { Ok(()) } else { Err(format!("pip uninstall failed: {:?}", status.code()).into()) }
+            let outdated = self.outdated()?;
+            if outdated.is_empty() {
+                return Ok(());
+            }
+            run_command(
+                &BackendCommand::new(pip_command())
+                    .args(["install", "--user", "--upgrade"])
+                    .args(outdated.iter().map(|p| p.name.as_str()))
+            )
+        }
+    }
+
+    fn installed_files(&self, package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        let output = if self.use_pipx {
+            run_command_output("pipx", &["runpip", package, "show", "-f", package])?
+        } else {
+            run_command_output(pip_command(), &["show", "-f", package])?
+        };
+        Ok(self.parse_show_files(&output))
+    }
+    
+    fn binaries(&self, package: &str) -> Result<Vec<PathBuf>, OneInstallError> {
+        if !self.use_pipx {
+            return Ok(executables_in_bin(self.installed_files(package)?));
+        }
+        let output = run_command_output("pipx", &["list", "--json"])?;
+        Ok(self.parse_pipx_apps(&output, package).into_iter().filter(|path| path.exists()).collect())
+    }
+
+    fn info(&self, package: &str) -> Result<Option<PackageInfo>, OneInstallError> {
+        // pipx keeps each package in its own venv, so ask that venv's pip
+        let show = if self.use_pipx {
+            run_command_output("pipx", &["runpip", package, "show", package])
+        } else {
+            run_command_output(pip_command(), &["show", package])
+        };
+        
+        let candidate = run_command_output(pip_command(), &["index", "versions", package])
+            .ok()
+            .and_then(|out| self.parse_search_output(&out).into_iter().next())
+            .and_then(|r| r.version);
+        
+        let mut info = match show.ok().and_then(|out| self.parse_show_output(&out)) {
+            Some(info) => info,
+            None if candidate.is_some() => PackageInfo::new(package, self.name()),
+            None => return Ok(None),
+        };
+        info.candidate_version = candidate;
+        
+        Ok(Some(info))
+    }
+}
+
+/// Resolve `..` in a path without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            other => normalized.push(other),
         }
     }
+    normalized
+}
+
+/// Prefer `pip3` over `pip` when both exist
+fn pip_command() -> &'static str {
+    if command_exists("pip3") { "pip3" } else { "pip" }
 }
 
 #[cfg(test)]
@@ -141,4 +362,39 @@ mod tests {
         let name = backend.name();
         assert!(name == "pip" || name == "pipx");
     }
+    
+    #[test]
+    fn test_parse_installed_output() {
+        let backend = PipBackend::new();
+        
+        let pipx = backend.parse_pipx_installed("black 23.1.0\nruff 0.1.5\n");
+        assert_eq!(pipx.len(), 2);
+        assert_eq!(pipx[0].version.as_deref(), Some("23.1.0"));
+        
+        let pip = backend.parse_pip_installed(r#"[{"name": "requests", "version": "2.31.0"}]"#);
+        assert_eq!(pip[0].name, "requests");
+        assert_eq!(pip[0].source, "pip");
+    }
+    
+    #[test]
+    fn test_parse_pip_outdated() {
+        let backend = PipBackend::new();
+        let output = r#"[{"name": "black", "version": "23.1.0", "latest_version": "24.1.0"}]"#;
+        let results = backend.parse_pip_outdated(output);
+        
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].current.as_deref(), Some("23.1.0"));
+        assert_eq!(results[0].latest.as_deref(), Some("24.1.0"));
+    }
+    
+    #[test]
+    fn test_parse_show_files() {
+        let backend = PipBackend::new();
+        let output = "Name: black\nVersion: 23.1.0\nLocation: /home/u/.local/lib/python3.11/site-packages\n\
+                      Requires: click\nFiles:\n  ../../../bin/black\n  black/__init__.py\n";
+        let files = backend.parse_show_files(output);
+        
+        assert_eq!(files[0], PathBuf::from("/home/u/.local/bin/black"));
+        assert_eq!(files[1], PathBuf::from("/home/u/.local/lib/python3.11/site-packages/black/__init__.py"));
+    }
 }
@@ -0,0 +1,130 @@
+//! Backend selection driven by `backends.priority` and `backends.disabled`
+
+use super::{backend_matches, get_all_available_backends, get_backend_for_context, Backend};
+use crate::config::{load_config, BackendConfig};
+use crate::context::OsContext;
+use crate::error::OneInstallError;
+
+/// Orders and filters backends according to the user's configuration
+///
+/// The OS's native package manager ranks first unless `backends.priority`
+/// lists it explicitly, so a priority list written for one OS doesn't push
+/// e.g. `dnf` behind `npm` on another.
+#[derive(Debug, Clone)]
+pub struct BackendResolver {
+    order: Vec<String>,
+    disabled: Vec<String>,
+}
+
+impl BackendResolver {
+    /// Build a resolver from backend config and the native backend's name
+    pub fn new(config: &BackendConfig, native: Option<&str>) -> Self {
+        let mut order = config.priority.clone();
+        if let Some(native) = native {
+            if !order.iter().any(|name| backend_matches(name, native)) {
+                order.insert(0, native.to_string());
+            }
+        }
+        order.retain(|name| !config.disabled.iter().any(|d| backend_matches(d, name)));
+
+        Self {
+            order,
+            disabled: config.disabled.clone(),
+        }
+    }
+
+    /// Build a resolver from the saved config and the detected OS
+    pub fn from_config() -> Self {
+        let config = load_config().unwrap_or_default();
+        let native = get_backend_for_context(&OsContext::detect()).ok();
+        Self::new(&config.backends, native.as_ref().map(|b| b.name()))
+    }
+
+    /// Enabled backends in ranking order (highest priority first)
+    pub fn priority(&self) -> &[String] {
+        &self.order
+    }
+
+    /// Whether a backend is turned off in the config
+    pub fn is_disabled(&self, name: &str) -> bool {
+        self.disabled.iter().any(|d| backend_matches(d, name))
+    }
+
+    /// Drop disabled backends and sort the rest by priority
+    ///
+    /// Backends the priority list doesn't mention keep their relative order
+    /// after the ones it does.
+    pub fn arrange(&self, backends: Vec<Box<dyn Backend>>) -> Vec<Box<dyn Backend>> {
+        let mut backends: Vec<Box<dyn Backend>> = backends.into_iter()
+            .filter(|b| !self.is_disabled(b.name()))
+            .collect();
+        backends.sort_by_key(|b| self.rank(b.name()));
+        backends
+    }
+
+    /// Every enabled backend installed on this system, by priority
+    pub fn available(&self) -> Vec<Box<dyn Backend>> {
+        self.arrange(get_all_available_backends())
+    }
+
+    /// A named backend, or the highest-priority available one
+    pub fn resolve(&self, name: Option<&str>) -> Result<Box<dyn Backend>, OneInstallError> {
+        match name {
+            Some(name) if self.is_disabled(name) => Err(OneInstallError::Config(format!(
+                "Backend '{}' is disabled (see backends.disabled)", name
+            ))),
+            Some(name) => get_all_available_backends().into_iter()
+                .find(|b| backend_matches(b.name(), name))
+                .ok_or_else(|| OneInstallError::BackendNotAvailable(name.to_string())),
+            None => self.available().into_iter().next().ok_or_else(|| {
+                OneInstallError::NoSupportedBackend(
+                    "No enabled package manager found; check backends.priority and backends.disabled".to_string()
+                )
+            }),
+        }
+    }
+
+    /// Position in the priority list (unlisted backends sort last)
+    fn rank(&self, name: &str) -> usize {
+        self.order.iter().position(|n| backend_matches(n, name)).unwrap_or(usize::MAX)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(priority: &[&str], disabled: &[&str]) -> BackendConfig {
+        BackendConfig {
+            priority: priority.iter().map(|s| s.to_string()).collect(),
+            disabled: disabled.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_native_backend_ranks_first_unless_listed() {
+        let fedora = BackendResolver::new(&config(&["apt", "brew", "npm"], &[]), Some("dnf"));
+        assert_eq!(fedora.priority(), ["dnf", "apt", "brew", "npm"]);
+
+        let linuxbrew = BackendResolver::new(&config(&["brew", "apt"], &[]), Some("apt"));
+        assert_eq!(linuxbrew.priority(), ["brew", "apt"]);
+    }
+
+    #[test]
+    fn test_disabled_backends_are_dropped() {
+        let resolver = BackendResolver::new(&config(&["apt", "snap", "npm"], &["snap"]), Some("apt"));
+        assert_eq!(resolver.priority(), ["apt", "npm"]);
+        assert!(resolver.is_disabled("snap"));
+        assert!(resolver.resolve(Some("snap")).is_err());
+    }
+
+    #[test]
+    fn test_pip_settings_apply_to_pipx() {
+        let resolver = BackendResolver::new(&config(&["pip", "npm"], &[]), Some("apt"));
+        assert!(resolver.rank("pipx") < resolver.rank("npm"));
+
+        let resolver = BackendResolver::new(&config(&["apt", "pipx"], &["pip"]), Some("apt"));
+        assert_eq!(resolver.priority(), ["apt"]);
+        assert!(resolver.is_disabled("pipx"));
+    }
+}
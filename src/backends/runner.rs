@@ -0,0 +1,482 @@
+//! Shared command execution layer for backends
+//!
+//! Every backend describes the native commands it wants to run as a
+//! [`BackendCommand`] and hands it to the process-wide [`CommandRunner`].
+//! Swapping the runner (e.g. for [`DryRunRunner`]) changes how every backend
+//! behaves without touching backend code.
+
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::io::{ErrorKind, Read};
+use std::path::PathBuf;
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::error::OneInstallError;
+
+/// A native command a backend wants to execute
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackendCommand {
+    /// Program to run (e.g. `apt-get`)
+    pub program: String,
+    /// Arguments passed to the program
+    pub args: Vec<String>,
+    /// Extra environment variables
+    pub env: Vec<(String, String)>,
+    /// Whether the command is wrapped in `sudo`
+    pub sudo: bool,
+    /// Working directory, if different from the current one
+    pub current_dir: Option<PathBuf>,
+    /// Treat a non-zero exit code as success (for tools that use it to signal results)
+    pub allow_nonzero_exit: bool,
+    /// Specific non-zero exit codes that still mean success
+    pub allowed_exit_codes: Vec<i32>,
+    /// Non-zero exit codes that mean success only when the command prints nothing
+    pub silent_exit_codes: Vec<i32>,
+}
+
+impl BackendCommand {
+    /// Create a new command for a program
+    pub fn new(program: impl Into<String>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+            env: Vec::new(),
+            sudo: false,
+            current_dir: None,
+            allow_nonzero_exit: false,
+            allowed_exit_codes: Vec::new(),
+            silent_exit_codes: Vec::new(),
+        }
+    }
+
+    /// Add a single argument
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Add several arguments
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Set an environment variable
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.env.push((key.into(), value.into()));
+        self
+    }
+
+    /// Run with elevated privileges (prefixes `sudo` unless already root)
+    pub fn sudo(mut self) -> Self {
+        self.sudo = !is_root();
+        self
+    }
+
+    /// Set the working directory
+    pub fn current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Accept any exit code (e.g. `npm outdated` exits 1 when updates exist)
+    pub fn allow_nonzero_exit(mut self) -> Self {
+        self.allow_nonzero_exit = true;
+        self
+    }
+
+    /// Accept one specific exit code (e.g. `dnf check-update` exits 100 when updates exist)
+    pub fn allow_exit_code(mut self, code: i32) -> Self {
+        self.allowed_exit_codes.push(code);
+        self
+    }
+
+    /// Accept an exit code only when stdout and stderr are both empty
+    /// (e.g. `pacman -Qu` exits 1 when nothing is outdated)
+    pub fn allow_silent_exit_code(mut self, code: i32) -> Self {
+        self.silent_exit_codes.push(code);
+        self
+    }
+
+    /// Whether a finished process counts as successful
+    fn accepts(&self, output: &std::process::Output) -> bool {
+        let code = output.status.code();
+        let silent = output.stdout.iter().all(u8::is_ascii_whitespace)
+            && output.stderr.iter().all(u8::is_ascii_whitespace);
+        output.status.success()
+            || self.allow_nonzero_exit
+            || code.is_some_and(|code| self.allowed_exit_codes.contains(&code))
+            || (silent && code.is_some_and(|code| self.silent_exit_codes.contains(&code)))
+    }
+
+    /// Short label used in error messages (e.g. `apt-get install`)
+    pub fn label(&self) -> String {
+        match self.args.first() {
+            Some(first) if !first.starts_with('-') => format!("{} {}", self.program, first),
+            _ => self.program.clone(),
+        }
+    }
+
+    /// Classify a failure to start the process
+    fn spawn_error(&self, e: std::io::Error) -> OneInstallError {
+        match e.kind() {
+            ErrorKind::NotFound if self.sudo => OneInstallError::BackendNotAvailable("sudo".to_string()),
+            ErrorKind::NotFound => OneInstallError::BackendNotAvailable(self.program.clone()),
+            ErrorKind::PermissionDenied => OneInstallError::PermissionDenied(self.to_string()),
+            _ => OneInstallError::Io(e),
+        }
+    }
+
+    /// Build the `std::process::Command` for this invocation
+    fn to_command(&self) -> Command {
+        let mut cmd = if self.sudo {
+            let mut cmd = Command::new("sudo");
+            cmd.arg(&self.program);
+            cmd
+        } else {
+            Command::new(&self.program)
+        };
+        cmd.args(&self.args);
+        for (key, value) in &self.env {
+            cmd.env(key, value);
+        }
+        if let Some(ref dir) = self.current_dir {
+            cmd.current_dir(dir);
+        }
+        cmd
+    }
+}
+
+impl fmt::Display for BackendCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref dir) = self.current_dir {
+            write!(f, "(cd {}) ", dir.display())?;
+        }
+        for (key, value) in &self.env {
+            write!(f, "{}={} ", key, value)?;
+        }
+        if self.sudo {
+            write!(f, "sudo ")?;
+        }
+        write!(f, "{}", self.program)?;
+        for arg in &self.args {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                write!(f, " \"{}\"", arg)?;
+            } else {
+                write!(f, " {}", arg)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Executes backend commands
+pub trait CommandRunner: Send + Sync {
+    /// Run a command with inherited stdio, failing on a non-zero exit code
+    fn run(&self, command: &BackendCommand) -> Result<(), OneInstallError>;
+
+    /// Run a read-only query and capture its stdout
+    fn output(&self, command: &BackendCommand) -> Result<String, OneInstallError>;
+
+    /// Whether this runner only records commands instead of executing them
+    fn is_dry_run(&self) -> bool {
+        false
+    }
+}
+
+/// Runner that executes commands on the host system
+#[derive(Debug, Default)]
+pub struct SystemRunner;
+
+impl CommandRunner for SystemRunner {
+    fn run(&self, command: &BackendCommand) -> Result<(), OneInstallError> {
+        println!("   Running: {}", command);
+
+        let status = command.to_command()
+            .status()
+            .map_err(|e| command.spawn_error(e))?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(OneInstallError::CommandFailed {
+                command: command.label(),
+                code: status.code(),
+                stderr: String::new(),
+            })
+        }
+    }
+
+    fn output(&self, command: &BackendCommand) -> Result<String, OneInstallError> {
+        let mut cmd = command.to_command();
+        cmd.stdin(Stdio::null());
+
+        let output = match current_deadline() {
+            Some((deadline, budget)) => {
+                let child = cmd.stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()
+                    .map_err(|e| command.spawn_error(e))?;
+                wait_with_deadline(child, deadline).ok_or_else(|| OneInstallError::TimedOut {
+                    command: command.label(),
+                    seconds: budget.as_secs(),
+                })??
+            }
+            None => cmd.output().map_err(|e| command.spawn_error(e))?,
+        };
+
+        if command.accepts(&output) {
+            Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            if stderr.contains("Permission denied") || stderr.contains("EACCES") || stderr.contains("are you root?") {
+                Err(OneInstallError::PermissionDenied(format!("{}: {}", command.label(), stderr.trim())))
+            } else {
+                Err(OneInstallError::CommandFailed {
+                    command: command.label(),
+                    code: output.status.code(),
+                    stderr,
+                })
+            }
+        }
+    }
+}
+
+/// Runner that records mutating commands instead of executing them
+///
+/// Read-only queries (`output`) still run so that searches and lookups
+/// produce real answers while planning.
+#[derive(Debug, Default)]
+pub struct DryRunRunner {
+    plan: Mutex<Vec<BackendCommand>>,
+}
+
+impl DryRunRunner {
+    /// Create an empty dry-run recorder
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Commands recorded so far, in execution order
+    pub fn plan(&self) -> Vec<BackendCommand> {
+        self.plan.lock().map(|p| p.clone()).unwrap_or_default()
+    }
+}
+
+impl CommandRunner for DryRunRunner {
+    fn run(&self, command: &BackendCommand) -> Result<(), OneInstallError> {
+        println!("   Would run: {}", command);
+        if let Ok(mut plan) = self.plan.lock() {
+            plan.push(command.clone());
+        }
+        Ok(())
+    }
+
+    fn output(&self, command: &BackendCommand) -> Result<String, OneInstallError> {
+        SystemRunner.output(command)
+    }
+
+    fn is_dry_run(&self) -> bool {
+        true
+    }
+}
+
+/// Wait for a child with piped output, killing it once `deadline` passes
+///
+/// Returns `None` if the child was killed. Output is drained on separate
+/// threads so a chatty child can't block on a full pipe while we poll.
+fn wait_with_deadline(mut child: Child, deadline: Instant) -> Option<std::io::Result<Output>> {
+    fn drain<R: Read + Send + 'static>(pipe: Option<R>) -> thread::JoinHandle<Vec<u8>> {
+        thread::spawn(move || {
+            let mut buf = Vec::new();
+            if let Some(mut pipe) = pipe {
+                let _ = pipe.read_to_end(&mut buf);
+            }
+            buf
+        })
+    }
+
+    let stdout = drain(child.stdout.take());
+    let stderr = drain(child.stderr.take());
+
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return None;
+            }
+            Ok(None) => thread::sleep(Duration::from_millis(20)),
+            Err(e) => return Some(Err(e)),
+        }
+    };
+
+    Some(Ok(Output {
+        status,
+        stdout: stdout.join().unwrap_or_default(),
+        stderr: stderr.join().unwrap_or_default(),
+    }))
+}
+
+thread_local! {
+    static DEADLINE: Cell<Option<(Instant, Duration)>> = const { Cell::new(None) };
+}
+
+fn current_deadline() -> Option<(Instant, Duration)> {
+    DEADLINE.with(Cell::get)
+}
+
+/// Run `f` with a time budget applied to every query it issues on this thread
+///
+/// Any [`CommandRunner::output`] call made by `f` after the budget is spent
+/// kills its child process and fails with [`OneInstallError::TimedOut`].
+pub fn with_timeout<T>(budget: Duration, f: impl FnOnce() -> T) -> T {
+    let previous = DEADLINE.with(|d| d.replace(Some((Instant::now() + budget, budget))));
+    let result = f();
+    DEADLINE.with(|d| d.set(previous));
+    result
+}
+
+static RUNNER: RwLock<Option<Arc<dyn CommandRunner>>> = RwLock::new(None);
+
+/// Replace the process-wide command runner
+pub fn set_runner(runner: Arc<dyn CommandRunner>) {
+    if let Ok(mut slot) = RUNNER.write() {
+        *slot = Some(runner);
+    }
+}
+
+/// Get the process-wide command runner (defaults to [`SystemRunner`])
+pub fn runner() -> Arc<dyn CommandRunner> {
+    RUNNER.read()
+        .ok()
+        .and_then(|slot| slot.clone())
+        .unwrap_or_else(|| Arc::new(SystemRunner))
+}
+
+/// Switch every backend to dry-run mode, returning the plan recorder
+pub fn enable_dry_run() -> Arc<DryRunRunner> {
+    let recorder = Arc::new(DryRunRunner::new());
+    set_runner(recorder.clone());
+    recorder
+}
+
+/// Whether backends are currently in dry-run mode
+pub fn is_dry_run() -> bool {
+    runner().is_dry_run()
+}
+
+/// Ask for the sudo password once, up front
+///
+/// Parallel installs would otherwise risk several prompts at once; after
+/// this, sudo's cached credentials cover them.
+pub fn prime_sudo() {
+    if !is_root() && !is_dry_run() && super::command_exists("sudo") {
+        let _ = Command::new("sudo").arg("-v").status();
+    }
+}
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Vec<BackendCommand>>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, also returning every mutating command it issued on this thread
+pub fn capture_commands<T>(f: impl FnOnce() -> T) -> (T, Vec<BackendCommand>) {
+    let previous = CAPTURED.with(|c| c.replace(Some(Vec::new())));
+    let result = f();
+    let captured = CAPTURED.with(|c| c.replace(previous)).unwrap_or_default();
+    (result, captured)
+}
+
+/// Run a mutating command through the active runner
+pub(crate) fn run_command(command: &BackendCommand) -> Result<(), OneInstallError> {
+    CAPTURED.with(|c| {
+        if let Some(ref mut commands) = *c.borrow_mut() {
+            commands.push(command.clone());
+        }
+    });
+    runner().run(command)
+}
+
+/// Check if running as root
+pub(crate) fn is_root() -> bool {
+    #[cfg(unix)]
+    {
+        unsafe { libc::geteuid() == 0 }
+    }
+
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_includes_env_and_sudo() {
+        let mut cmd = BackendCommand::new("apt-get")
+            .args(["install", "-y", "jq"])
+            .env("DEBIAN_FRONTEND", "noninteractive");
+        cmd.sudo = true;
+
+        assert_eq!(cmd.to_string(), "DEBIAN_FRONTEND=noninteractive sudo apt-get install -y jq");
+        assert_eq!(cmd.label(), "apt-get install");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_query_killed_after_timeout() {
+        let cmd = BackendCommand::new("sleep").arg("5");
+        let start = Instant::now();
+
+        let result = with_timeout(Duration::from_millis(100), || SystemRunner.output(&cmd));
+
+        assert!(matches!(result, Err(OneInstallError::TimedOut { .. })));
+        assert!(start.elapsed() < Duration::from_secs(2));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_only_listed_exit_codes_are_accepted() {
+        let sh = |script: &str| BackendCommand::new("sh").args(["-c", script]);
+
+        assert_eq!(SystemRunner.output(&sh("echo jq; exit 100").allow_exit_code(100)).unwrap(), "jq\n");
+        assert!(SystemRunner.output(&sh("exit 1").allow_exit_code(100)).is_err());
+
+        assert_eq!(SystemRunner.output(&sh("exit 1").allow_silent_exit_code(1)).unwrap(), "");
+        assert!(SystemRunner.output(&sh("echo locked >&2; exit 1").allow_silent_exit_code(1)).is_err());
+        assert!(SystemRunner.output(&sh("exit 2").allow_silent_exit_code(1)).is_err());
+    }
+
+    #[test]
+    fn test_capture_commands() {
+        let cmd = BackendCommand::new("1install-no-such-program").arg("install");
+
+        let (result, captured) = capture_commands(|| run_command(&cmd));
+
+        // Failed commands are captured too
+        assert!(matches!(result, Err(OneInstallError::BackendNotAvailable(_))));
+        assert_eq!(captured, vec![cmd]);
+    }
+
+    #[test]
+    fn test_dry_run_records_plan() {
+        let runner = DryRunRunner::new();
+        let cmd = BackendCommand::new("npm").args(["install", "-g", "prettier"]);
+
+        runner.run(&cmd).unwrap();
+
+        assert!(runner.is_dry_run());
+        assert_eq!(runner.plan(), vec![cmd]);
+    }
+}
@@ -1,8 +1,11 @@
 //! Snap backend for Linux
 
-use super::{Backend, command_exists};
+use super::{
+    Backend, BackendCommand, OutdatedPackage, PackageInfo,
+    command_exists, run_command, run_command_output, parse_fields,
+};
 use crate::search::PackageResult;
-use std::process::Command;
+use crate::error::OneInstallError;
 
 pub struct SnapBackend;
 
@@ -10,6 +13,66 @@ impl SnapBackend {
     pub fn new() -> Self {
         Self
     }
+    
+    /// Parse `snap list` output (header, then "Name Version Rev Tracking Publisher Notes")
+    fn parse_installed_output(&self, output: &str) -> Vec<PackageResult> {
+        output.lines()
+            .skip(1)
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() >= 2 {
+                    Some(PackageResult::new(parts[0].to_string(), "snap".to_string()).with_version(parts[1]))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+    
+    /// Parse `snap refresh --list` output (header, then "Name Version Rev ...")
+    fn parse_outdated_output(&self, output: &str) -> Vec<OutdatedPackage> {
+        output.lines()
+            .skip(1)
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() >= 2 {
+                    Some(OutdatedPackage::new(parts[0], "snap").with_latest(parts[1]))
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+    
+    /// Parse `snap info` output into a PackageInfo
+    fn parse_info_output(&self, output: &str) -> Option<PackageInfo> {
+        let fields = parse_fields(output);
+        let field = |key: &str| fields.get(key).filter(|v| !v.is_empty()).cloned();
+        
+        let mut info = PackageInfo::new(field("name")?, "snap");
+        info.description = field("summary");
+        info.maintainer = field("publisher");
+        info.license = field("license").filter(|l| l != "unset");
+        info.homepage = field("contact").or_else(|| field("website"));
+        info.candidate_version = field("latest/stable")
+            .and_then(|v| v.split_whitespace().next().map(String::from));
+        
+        // "installed:   1.7.1   (1234) 3MB -"
+        if let Some(installed) = field("installed") {
+            let parts: Vec<&str> = installed.split_whitespace().collect();
+            info.installed_version = parts.first().map(|v| v.to_string());
+            info.size = parts.get(2).map(|v| v.to_string());
+        }
+        
+        // "commands:" is followed by an indented "  - name" list
+        info.binaries = output.lines()
+            .skip_while(|line| !line.starts_with("commands:"))
+            .skip(1)
+            .take_while(|line| line.trim_start().starts_with("- "))
+            .map(|line| line.trim_start().trim_start_matches("- ").to_string())
+            .collect();
+        Some(info)
+    }
 }
 
 impl Default for SnapBackend {
@@ -27,13 +90,14 @@ impl Backend for SnapBackend {
         command_exists("snap")
     }
     
-    fn search(&self, query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>> {
-        let output = Command::new("snap")
-            .args(["find", query])
-            .output()?;
-            
+    fn needs_root(&self) -> bool {
+        true
+    }
+    
+    fn search(&self, query: &str) -> Result<Vec<PackageResult>, OneInstallError> {
+        // `snap find` exits non-zero when nothing matches
+        let stdout = run_command_output("snap", &["find", query]).unwrap_or_default();
         let mut results = Vec::new();
-        let stdout = String::from_utf8_lossy(&output.stdout);
         
         let mut lines = stdout.lines();
         let _header = lines.next();
@@ -54,45 +118,40 @@ impl Backend for SnapBackend {
         Ok(results)
     }
     
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: sudo snap install {}", package);
-        
-        let status = Command::new("sudo")
-            .args(["snap", "install", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("snap install failed with exit code: {:?}", status.code()).into())
-        }
+    fn install(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("snap").args(["install", package]).sudo())
     }
 
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: sudo snap refresh {}", package);
-        
-        let status = Command::new("sudo")
-            .args(["snap", "refresh", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("snap refresh failed with exit code: {:?}", status.code()).into())
-        }
+    fn install_many(&self, packages: &[&str]) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("snap").arg("install").args(packages.iter().copied()).sudo())
     }
 
-    fn uninstall(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: sudo snap remove {}", package);
-        
-        let status = Command::new("sudo")
-            .args(["snap", "remove", package])
-            .status()?;
-            
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("snap remove failed with exit code: {:?}", status.code()).into())
+    fn update(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("snap").args(["refresh", package]).sudo())
+    }
+
+    fn uninstall(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("snap").args(["remove", package]).sudo())
+    }
+
+    fn list_installed(&self) -> Result<Vec<PackageResult>, OneInstallError> {
+        let output = run_command_output("snap", &["list"])?;
+        Ok(self.parse_installed_output(&output))
+    }
+
+    fn outdated(&self) -> Result<Vec<OutdatedPackage>, OneInstallError> {
+        let output = run_command_output("snap", &["refresh", "--list"])?;
+        Ok(self.parse_outdated_output(&output))
+    }
+
+    fn upgrade_all(&self) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("snap").arg("refresh").sudo())
+    }
+
+    fn info(&self, package: &str) -> Result<Option<PackageInfo>, OneInstallError> {
+        match run_command_output("snap", &["info", package]) {
+            Ok(output) => Ok(self.parse_info_output(&output)),
+            Err(_) => Ok(None),
         }
     }
 }
@@ -0,0 +1,257 @@
+//! Package specs: `[backend:]name[@version]`
+//!
+//! ```text
+//! jq                 any version, default backend
+//! npm:prettier       pinned backend
+//! pip:black@23.1     exact version
+//! ripgrep@14.*       any 14.x
+//! npm:eslint@^8.50   range, passed to backends that understand it
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+use crate::error::OneInstallError;
+
+/// Names of every backend 1install knows, available or not
+pub const BACKEND_NAMES: &[&str] = &[
+    "winget", "apt", "brew", "npm", "pip", "git", "pacman", "dnf", "snap", "flatpak", "cargo", "go",
+];
+
+/// Name a backend is known by in specs and config; the Python backend
+/// reports itself as `pipx` when pipx is installed, but `pip` names it
+/// either way
+pub fn canonical_backend_name(name: &str) -> &str {
+    match name {
+        "pipx" => "pip",
+        _ => name,
+    }
+}
+
+/// Whether two backend names refer to the same backend
+pub fn backend_matches(a: &str, b: &str) -> bool {
+    canonical_backend_name(a) == canonical_backend_name(b)
+}
+
+/// Whether `name` is a known backend name, including aliases like `pipx`
+pub fn is_backend_name(name: &str) -> bool {
+    BACKEND_NAMES.contains(&canonical_backend_name(name))
+}
+
+/// Broad family a backend belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    /// The OS's own package manager (apt, dnf, pacman, brew, winget)
+    System,
+    /// Distribution-independent app stores (snap, flatpak)
+    Universal,
+    /// A language ecosystem's registry (npm, pip, cargo, go)
+    Language,
+    /// Built from source (git)
+    Source,
+}
+
+impl BackendKind {
+    /// Classify a backend by name
+    pub fn of(backend: &str) -> Self {
+        match backend {
+            "snap" | "flatpak" => BackendKind::Universal,
+            "npm" | "pip" | "pipx" | "cargo" | "go" => BackendKind::Language,
+            "git" => BackendKind::Source,
+            _ => BackendKind::System,
+        }
+    }
+}
+
+impl fmt::Display for BackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BackendKind::System => "system package manager",
+            BackendKind::Universal => "universal package manager",
+            BackendKind::Language => "language package manager",
+            BackendKind::Source => "source build",
+        })
+    }
+}
+
+/// A version requirement attached to a package spec
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VersionReq {
+    /// `1.2.3` (or `=1.2.3`)
+    Exact(String),
+    /// `1.2.*` or `1.2.x`: any version starting with `1.2.`
+    Prefix(String),
+    /// Anything else (`^1.2`, `~1.2`, `>=1.2,<2`), kept verbatim
+    Range(String),
+}
+
+impl VersionReq {
+    /// Classify a version string; `None` for "any version" (`*` or empty)
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        if text.is_empty() || text == "*" {
+            return None;
+        }
+        if let Some(prefix) = text.strip_suffix(".*").or_else(|| text.strip_suffix(".x")) {
+            if !prefix.contains(['*', ',', ' ', '<', '>', '^', '~', '=']) {
+                return Some(VersionReq::Prefix(prefix.to_string()));
+            }
+        }
+        let exact = text.strip_prefix('=').filter(|rest| !rest.starts_with('='))
+            .unwrap_or(text);
+        if exact.starts_with(['^', '~', '>', '<', '=', '!']) || exact.contains(['*', ',', ' ', '|']) {
+            Some(VersionReq::Range(text.to_string()))
+        } else {
+            Some(VersionReq::Exact(exact.to_string()))
+        }
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionReq::Exact(version) => write!(f, "{}", version),
+            VersionReq::Prefix(prefix) => write!(f, "{}.*", prefix),
+            VersionReq::Range(range) => write!(f, "{}", range),
+        }
+    }
+}
+
+/// A package as typed on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSpec {
+    /// Backend named with `backend:`, if any
+    pub backend: Option<String>,
+    /// Native package name
+    pub name: String,
+    /// Version requirement from `@version`, if any
+    pub version: Option<VersionReq>,
+}
+
+impl PackageSpec {
+    /// Backend for this spec, checking it against a `--backend` flag
+    pub fn backend_or<'a>(&'a self, flag: Option<&'a str>) -> Result<Option<&'a str>, OneInstallError> {
+        match (self.backend.as_deref(), flag) {
+            (Some(own), Some(flag)) if !backend_matches(own, flag) => Err(OneInstallError::InvalidInput(format!(
+                "{} names backend {} but --backend is {}", self, own, flag
+            ))),
+            (own, flag) => Ok(own.or(flag)),
+        }
+    }
+
+    /// Fail if the spec carries a version, for commands that can't use one
+    pub fn without_version(&self, command: &str) -> Result<&Self, OneInstallError> {
+        match self.version {
+            Some(ref version) => Err(OneInstallError::InvalidInput(format!(
+                "{} does not take a version (got {}@{})", command, self.name, version
+            ))),
+            None => Ok(self),
+        }
+    }
+}
+
+impl FromStr for PackageSpec {
+    type Err = OneInstallError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+
+        // Only known backend names count as a prefix, so `libc6:i386` and
+        // URLs keep their colon
+        let (backend, rest) = match text.split_once(':') {
+            Some((backend, rest)) if is_backend_name(backend) => (Some(backend.to_string()), rest),
+            _ => (None, text),
+        };
+
+        // Split off `@version`, leaving npm scopes (`@scope/pkg`) and git
+        // remotes (`git@host:repo`) alone
+        let is_url = rest.contains("://") || rest.starts_with("git@");
+        let (name, version) = match rest.rfind('@') {
+            Some(at) if at > 0 && !is_url => {
+                let version = &rest[at + 1..];
+                if version.is_empty() {
+                    return Err(OneInstallError::InvalidInput(format!("Missing version after '@' in {}", text)));
+                }
+                (&rest[..at], VersionReq::parse(version))
+            }
+            _ => (rest, None),
+        };
+
+        if name.is_empty() {
+            return Err(OneInstallError::InvalidInput(format!("Missing package name in '{}'", text)));
+        }
+        Ok(PackageSpec { backend, name: name.to_string(), version })
+    }
+}
+
+impl fmt::Display for PackageSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(ref backend) = self.backend {
+            write!(f, "{}:", backend)?;
+        }
+        write!(f, "{}", self.name)?;
+        if let Some(ref version) = self.version {
+            write!(f, "@{}", version)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(text: &str) -> PackageSpec {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_backend_and_version() {
+        assert_eq!(spec("jq"), PackageSpec { backend: None, name: "jq".into(), version: None });
+
+        let black = spec("pip:black@23.1");
+        assert_eq!(black.backend.as_deref(), Some("pip"));
+        assert_eq!(black.name, "black");
+        assert_eq!(black.version, Some(VersionReq::Exact("23.1".into())));
+
+        assert_eq!(spec("ripgrep@14.*").version, Some(VersionReq::Prefix("14".into())));
+        assert_eq!(spec("npm:eslint@^8.50").version, Some(VersionReq::Range("^8.50".into())));
+        assert_eq!(spec("black@>=23,<24").version, Some(VersionReq::Range(">=23,<24".into())));
+        assert_eq!(spec("jq@=1.7").version, Some(VersionReq::Exact("1.7".into())));
+        assert_eq!(spec("jq@*").version, None);
+        assert_eq!(spec("pip:black@23.1").to_string(), "pip:black@23.1");
+    }
+
+    #[test]
+    fn test_names_that_contain_separators() {
+        let scoped = spec("npm:@angular/cli@17.0.0");
+        assert_eq!(scoped.name, "@angular/cli");
+        assert_eq!(scoped.version, Some(VersionReq::Exact("17.0.0".into())));
+        assert_eq!(spec("@angular/cli").name, "@angular/cli");
+
+        assert_eq!(spec("libc6:i386"), PackageSpec { backend: None, name: "libc6:i386".into(), version: None });
+        assert_eq!(spec("git:git@github.com:user/repo").name, "git@github.com:user/repo");
+        assert_eq!(spec("go:golang.org/x/tools/gopls@v0.14.0").version, Some(VersionReq::Exact("v0.14.0".into())));
+
+        assert!("jq@".parse::<PackageSpec>().is_err());
+        assert!("npm:".parse::<PackageSpec>().is_err());
+    }
+
+    #[test]
+    fn test_backend_flag_conflict() {
+        assert_eq!(spec("npm:prettier").backend_or(None).unwrap(), Some("npm"));
+        assert_eq!(spec("prettier").backend_or(Some("npm")).unwrap(), Some("npm"));
+        assert!(spec("npm:prettier").backend_or(Some("apt")).is_err());
+        assert!(spec("pip:black").backend_or(Some("pipx")).is_ok());
+    }
+
+    #[test]
+    fn test_pip_and_pipx_name_one_backend() {
+        let black = spec("pipx:black@23.1");
+        assert_eq!(black.backend.as_deref(), Some("pipx"));
+        assert_eq!(black.name, "black");
+        assert!(backend_matches("pip", "pipx"));
+        assert!(backend_matches("pipx", "pip"));
+        assert!(!backend_matches("pip", "npm"));
+        assert!(is_backend_name("pipx"));
+    }
+}
@@ -1,8 +1,11 @@
 //! Winget backend for Windows
 
-use super::{Backend, command_exists, run_command_output};
+use super::{
+    Backend, BackendCommand, PackageInfo,
+    command_exists, run_command, run_command_output, parse_fields,
+};
 use crate::search::PackageResult;
-use std::process::Command;
+use crate::error::OneInstallError;
 
 /// Windows Package Manager (winget) backend
 pub struct WingetBackend;
@@ -69,67 +72,76 @@ impl Backend for WingetBackend {
         command_exists("winget")
     }
     
-    fn search(&self, query: &str) -> Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>> {
+    fn search(&self, query: &str) -> Result<Vec<PackageResult>, OneInstallError> {
         let output = run_command_output("winget", &["search", query, "--accept-source-agreements"])?;
         Ok(self.parse_search_output(&output))
     }
     
-    fn install(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: winget install {} --accept-source-agreements --accept-package-agreements", package);
-        
-        let status = Command::new("winget")
-            .args([
-                "install",
-                package,
-                "-e",
-                "--accept-source-agreements",
-                "--accept-package-agreements",
-            ])
-            .status()?;
-        
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("winget install failed with exit code: {:?}", status.code()).into())
-        }
+    fn install(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("winget").args([
+            "install",
+            package,
+            "-e",
+            "--accept-source-agreements",
+            "--accept-package-agreements",
+        ]))
     }
 
-    fn update(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: winget upgrade {} --accept-source-agreements", package);
-        
-        let status = Command::new("winget")
-            .args([
-                "upgrade",
-                package,
-                "-e",
-                "--accept-source-agreements",
-            ])
-            .status()?;
-        
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("winget upgrade failed with exit code: {:?}", status.code()).into())
-        }
+    fn install_version(&self, package: &str, version: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("winget").args([
+            "install",
+            package,
+            "-e",
+            "--version",
+            version,
+            "--accept-source-agreements",
+            "--accept-package-agreements",
+        ]))
     }
 
-    fn uninstall(&self, package: &str) -> Result<(), Box<dyn std::error::Error>> {
-        println!("   Running: winget uninstall {} --accept-source-agreements", package);
+    fn update(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("winget").args([
+            "upgrade",
+            package,
+            "-e",
+            "--accept-source-agreements",
+        ]))
+    }
+
+    fn uninstall(&self, package: &str) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("winget").args([
+            "uninstall",
+            package,
+            "-e",
+            "--accept-source-agreements",
+        ]))
+    }
+
+    fn upgrade_all(&self) -> Result<(), OneInstallError> {
+        run_command(&BackendCommand::new("winget").args([
+            "upgrade",
+            "--all",
+            "--accept-source-agreements",
+            "--accept-package-agreements",
+        ]))
+    }
+
+    fn info(&self, package: &str) -> Result<Option<PackageInfo>, OneInstallError> {
+        let output = match run_command_output("winget", &["show", package, "-e", "--accept-source-agreements"]) {
+            Ok(output) => output,
+            Err(_) => return Ok(None),
+        };
+        let fields = parse_fields(&output);
+        let field = |key: &str| fields.get(key).filter(|v| !v.is_empty()).cloned();
         
-        let status = Command::new("winget")
-            .args([
-                "uninstall",
-                package,
-                "-e",
-                "--accept-source-agreements",
-            ])
-            .status()?;
+        let mut info = PackageInfo::new(package, "winget");
+        info.candidate_version = field("Version");
+        info.description = field("Description");
+        info.homepage = field("Homepage");
+        info.license = field("License");
+        info.maintainer = field("Publisher");
         
-        if status.success() {
-            Ok(())
-        } else {
-            Err(format!("winget uninstall failed with exit code: {:?}", status.code()).into())
-        }
+        Ok(Some(info))
     }
 }
 
@@ -0,0 +1,147 @@
+//! 1i-shim - compiled shim executable
+//!
+//! Hardlinked into the shim directory under each shim's name when
+//! `shims.mode` is `native`. It works out which shim it is from the name it
+//! was run under, looks that name up in the shim registry and hands over to
+//! the target with the shim's environment, working directory and arguments
+//! followed by the caller's.
+//!
+//! Every shimmed command starts here, so this binary deliberately stays
+//! independent of the `oneinstall` library: it reads `shims.toml` itself and
+//! needs only `serde`, `toml` and `dirs` besides std.
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// The fields of a `shims.toml` entry the shim needs
+///
+/// Mirrors `oneinstall::shims::ShimEntry`; other fields are ignored.
+#[derive(Debug, Deserialize)]
+struct ShimEntry {
+    target: PathBuf,
+    #[serde(default)]
+    env: BTreeMap<String, String>,
+    #[serde(default)]
+    args: Vec<String>,
+    /// `inherit`, `target` or an absolute path
+    #[serde(default)]
+    cwd: Option<String>,
+}
+
+impl ShimEntry {
+    /// Directory to change to before running the target, if any
+    fn working_dir(&self) -> Option<PathBuf> {
+        match self.cwd.as_deref() {
+            None | Some("inherit") => None,
+            Some("target") => self.target.parent().map(Path::to_path_buf),
+            Some(path) => Some(PathBuf::from(path)),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ShimRegistry {
+    #[serde(default)]
+    shims: HashMap<String, ShimEntry>,
+}
+
+impl ShimRegistry {
+    fn load() -> Result<Self, String> {
+        let home = dirs::home_dir().ok_or("could not find home directory")?;
+        let path = home.join(".local").join("share").join("1install").join("shims.toml");
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        toml::from_str(&content).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+}
+
+/// Shim name from the path the executable was run under
+fn shim_name(argv0: &OsStr) -> Option<String> {
+    let path = Path::new(argv0);
+    let name = if cfg!(windows) { path.file_stem() } else { path.file_name() };
+    name.map(|n| n.to_string_lossy().into_owned())
+}
+
+/// Run the shim's target; returns its exit code where `exec` isn't available
+fn run_shim(argv0: &OsStr, args: Vec<OsString>) -> Result<i32, String> {
+    let name = shim_name(argv0).unwrap_or_default();
+    if name == "1i-shim" {
+        return Err("must be run through a link in the shim directory (see '1i shims list')".to_string());
+    }
+
+    let registry = ShimRegistry::load()?;
+    let entry = registry.shims.get(&name)
+        .ok_or_else(|| format!("No shim named '{}'; run '1i shims refresh'", name))?;
+
+    let mut command = Command::new(&entry.target);
+    command.args(&entry.args).args(args).envs(&entry.env);
+    if let Some(dir) = entry.working_dir() {
+        // Keep PWD in step, as `cd` in a script shim would
+        command.env("PWD", &dir).current_dir(dir);
+    }
+
+    let spawn_error = |e: std::io::Error| format!("{}: {}", entry.target.display(), e);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        Err(spawn_error(command.exec()))
+    }
+
+    #[cfg(not(unix))]
+    {
+        Ok(command.status().map_err(spawn_error)?.code().unwrap_or(1))
+    }
+}
+
+fn main() {
+    let mut args = std::env::args_os();
+    let argv0 = args.next().unwrap_or_default();
+
+    match run_shim(&argv0, args.collect()) {
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            eprintln!("1i-shim: {}", e);
+            std::process::exit(127);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shim_name_from_argv0() {
+        assert_eq!(shim_name(OsStr::new("/home/u/.local/share/1install/shims/rg")).as_deref(), Some("rg"));
+        assert_eq!(shim_name(OsStr::new("fd")).as_deref(), Some("fd"));
+        assert_eq!(shim_name(OsStr::new("")), None);
+    }
+
+    #[test]
+    fn test_reads_registry_entries() {
+        let registry: ShimRegistry = toml::from_str(r#"
+            [shims.rg]
+            name = "rg"
+            target = "/usr/bin/rg"
+            installed_by = "apt"
+            created_at = "2024-01-01T00:00:00Z"
+            args = ["--smart-case"]
+            cwd = "target"
+
+            [shims.rg.env]
+            RIPGREP_CONFIG_PATH = "/etc/rgrc"
+        "#).unwrap();
+
+        let rg = &registry.shims["rg"];
+        assert_eq!(rg.args, ["--smart-case"]);
+        assert_eq!(rg.env["RIPGREP_CONFIG_PATH"], "/etc/rgrc");
+        assert_eq!(rg.working_dir(), Some(PathBuf::from("/usr/bin")));
+    }
+}
@@ -1,6 +1,11 @@
 //! CLI command definitions using clap
 
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use super::OutputFormat;
+use crate::backends::PackageSpec;
+use crate::config::InstallPolicy;
+use crate::shims::WorkingDir;
 
 /// 1install - Unified cross-platform package manager
 #[derive(Parser, Debug)]
@@ -10,6 +15,14 @@ use clap::{Parser, Subcommand};
 #[command(about = "Unified cross-platform package manager", long_about = None)]
 #[command(propagate_version = true)]
 pub struct Cli {
+    /// Print the native commands that would run without executing them
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    /// Output format for command results
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -26,13 +39,25 @@ pub enum Commands {
         /// Maximum number of results to display
         #[arg(short, long, default_value = "20")]
         limit: usize,
+        
+        /// Show each backend's results as soon as it finishes
+        #[arg(long)]
+        stream: bool,
+        
+        /// Ignore cached results and query every backend again
+        #[arg(long, conflicts_with = "offline")]
+        refresh: bool,
+        
+        /// Answer from cached results only, without running any backend
+        #[arg(long)]
+        offline: bool,
     },
     
-    /// Install a package
+    /// Install one or more packages
     Install {
-        /// Name of the package to install
-        #[arg(value_name = "PACKAGE")]
-        package: String,
+        /// Packages to install, as `[backend:]name[@version]`
+        #[arg(value_name = "PACKAGE", required = true)]
+        packages: Vec<PackageSpec>,
         
         /// Specify which backend to use (e.g., apt, winget, npm)
         #[arg(short, long)]
@@ -41,6 +66,62 @@ pub enum Commands {
         /// Expected SHA-256 hash of the package (binary) for integrity verification
         #[arg(long, value_name = "HASH")]
         verify: Option<String>,
+        
+        /// Roll back every package if any of them fails to install
+        #[arg(long)]
+        atomic: bool,
+        
+        /// Don't try other backends when the first one cannot install a package
+        #[arg(long)]
+        no_fallback: bool,
+        
+        /// Search every backend and install from the best exact match
+        #[arg(long)]
+        smart: bool,
+        
+        /// Which backend --smart prefers: system, native or newest (implies --smart)
+        #[arg(long, value_name = "POLICY")]
+        prefer: Option<InstallPolicy>,
+    },
+    
+    /// Show detailed information about a package, side by side across backends
+    Info {
+        /// Package to inspect, as `[backend:]name`
+        #[arg(value_name = "PACKAGE")]
+        package: PackageSpec,
+        
+        /// Only query this backend (e.g., apt, npm)
+        #[arg(short, long)]
+        backend: Option<String>,
+    },
+    
+    /// List installed packages across all available backends
+    List {
+        /// Only list packages from this backend (e.g., apt, npm)
+        #[arg(short, long)]
+        backend: Option<String>,
+    },
+    
+    /// List installed packages with newer versions available
+    Outdated {
+        /// Only check this backend (e.g., apt, npm)
+        #[arg(short, long)]
+        backend: Option<String>,
+    },
+    
+    /// Upgrade a package, or everything with --all
+    Upgrade {
+        /// Package to upgrade, as `[backend:]name[@version]`
+        #[arg(value_name = "PACKAGE", required_unless_present = "all")]
+        package: Option<PackageSpec>,
+        
+        /// Upgrade every outdated package across all available backends
+        #[arg(long, conflicts_with = "package")]
+        all: bool,
+        
+        /// Specify which backend to use
+        #[arg(short, long)]
+        backend: Option<String>,
     },
     
     /// List available backends on this system
@@ -60,9 +141,9 @@ pub enum Commands {
     
     /// Update a package to the latest version
     Update {
-        /// Name of the package to update
+        /// Package to update, as `[backend:]name[@version]`
         #[arg(value_name = "PACKAGE")]
-        package: String,
+        package: PackageSpec,
         
         /// Specify which backend to use
         #[arg(short, long)]
@@ -71,15 +152,51 @@ pub enum Commands {
     
     /// Uninstall a package
     Uninstall {
-        /// Name of the package to uninstall
+        /// Package to uninstall, as `[backend:]name`
         #[arg(value_name = "PACKAGE")]
-        package: String,
+        package: PackageSpec,
         
         /// Specify which backend to use
         #[arg(short, long)]
         backend: Option<String>,
     },
     
+    /// Install everything listed in the project's 1install.toml
+    Sync {
+        /// Path to the manifest (defaults to the nearest 1install.toml)
+        #[arg(short, long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
+        
+        /// Install the exact versions in 1install.lock and fail on any drift
+        #[arg(long)]
+        locked: bool,
+    },
+    
+    /// Record exact installed versions and binary hashes in 1install.lock
+    Lock {
+        /// Path to the manifest (defaults to the nearest 1install.toml)
+        #[arg(short, long, value_name = "FILE")]
+        manifest: Option<PathBuf>,
+    },
+    
+    /// Show the journal of installs, updates and uninstalls
+    History {
+        /// Only show entries for this package
+        #[arg(value_name = "PACKAGE")]
+        package: Option<String>,
+        
+        /// Maximum number of entries to display (most recent)
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+    
+    /// Reverse a journaled change (defaults to the most recent one)
+    Undo {
+        /// Journal entry id, as shown by `1i history`
+        #[arg(value_name = "ID")]
+        id: Option<u64>,
+    },
+    
     /// Install 1install to the local system (bootstrap)
     SelfInstall,
     
@@ -117,6 +234,59 @@ pub enum ShimsAction {
     Path,
     /// Show PATH setup instructions
     Setup,
+    /// Rebuild every shim, retargeting moved binaries and removing dead ones
+    Refresh,
+    /// Create a shim for an existing binary
+    Add {
+        /// Command on PATH or path to the binary
+        binary: String,
+        /// Shim name (defaults to the binary's name)
+        #[arg(long = "as", value_name = "NAME")]
+        name: Option<String>,
+    },
+    /// Remove a shim
+    Remove {
+        /// Shim name
+        name: String,
+    },
+    /// Expose a shim under a different name
+    Rename {
+        /// Current shim name
+        from: String,
+        /// New shim name
+        to: String,
+    },
+    /// Pin a shim to its current target so refresh never retargets it
+    Pin {
+        /// Shim name
+        name: String,
+        /// Let refresh retarget the shim again
+        #[arg(long)]
+        unpin: bool,
+    },
+    /// Set environment variables for a shim's target (`KEY=` unsets)
+    SetEnv {
+        /// Shim name
+        name: String,
+        /// Variables to set
+        #[arg(value_name = "KEY=VAL", required = true)]
+        vars: Vec<String>,
+    },
+    /// Set arguments passed to a shim's target before the caller's (none clears)
+    SetArgs {
+        /// Shim name
+        name: String,
+        /// Arguments to prepend
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+    /// Set the directory a shim's target runs in
+    SetCwd {
+        /// Shim name
+        name: String,
+        /// `inherit` (default), `target` (the binary's directory) or an absolute path
+        cwd: WorkingDir,
+    },
 }
 
 #[cfg(test)]
@@ -139,4 +309,153 @@ mod tests {
             _ => panic!("Expected Config Get command"),
         }
     }
+
+    #[test]
+    fn test_shims_refresh() {
+        let cli = Cli::parse_from(["1i", "shims", "refresh"]);
+        assert!(matches!(cli.command, Commands::Shims { action: ShimsAction::Refresh }));
+    }
+
+    #[test]
+    fn test_shims_add_alias() {
+        match Cli::parse_from(["1i", "shims", "add", "/usr/bin/fdfind", "--as", "fd"]).command {
+            Commands::Shims { action: ShimsAction::Add { binary, name } } => {
+                assert_eq!(binary, "/usr/bin/fdfind");
+                assert_eq!(name.as_deref(), Some("fd"));
+            }
+            _ => panic!("Expected Shims Add command"),
+        }
+    }
+
+    #[test]
+    fn test_shims_set_args_accepts_flags() {
+        match Cli::parse_from(["1i", "shims", "set-args", "rg", "--smart-case", "-L"]).command {
+            Commands::Shims { action: ShimsAction::SetArgs { name, args } } => {
+                assert_eq!(name, "rg");
+                assert_eq!(args, ["--smart-case", "-L"]);
+            }
+            _ => panic!("Expected Shims SetArgs command"),
+        }
+        assert!(Cli::try_parse_from(["1i", "shims", "set-cwd", "rg", "relative"]).is_err());
+    }
+
+    #[test]
+    fn test_list_backend_filter() {
+        let cli = Cli::parse_from(["1i", "list", "--backend", "npm"]);
+        match cli.command {
+            Commands::List { backend } => assert_eq!(backend.as_deref(), Some("npm")),
+            _ => panic!("Expected List command"),
+        }
+    }
+
+    #[test]
+    fn test_upgrade_all() {
+        let cli = Cli::parse_from(["1i", "upgrade", "--all"]);
+        match cli.command {
+            Commands::Upgrade { package, all, .. } => {
+                assert!(all);
+                assert!(package.is_none());
+            }
+            _ => panic!("Expected Upgrade command"),
+        }
+        assert!(Cli::try_parse_from(["1i", "upgrade"]).is_err());
+    }
+
+    #[test]
+    fn test_search_refresh_conflicts_with_offline() {
+        assert!(Cli::try_parse_from(["1i", "search", "jq", "--refresh"]).is_ok());
+        assert!(Cli::try_parse_from(["1i", "search", "jq", "--refresh", "--offline"]).is_err());
+    }
+
+    #[test]
+    fn test_search_stream() {
+        let cli = Cli::parse_from(["1i", "search", "ripgrep", "--stream"]);
+        match cli.command {
+            Commands::Search { query, stream, .. } => {
+                assert_eq!(query, "ripgrep");
+                assert!(stream);
+            }
+            _ => panic!("Expected Search command"),
+        }
+    }
+
+    #[test]
+    fn test_global_output_format() {
+        let cli = Cli::parse_from(["1i", "search", "jq", "--output", "json"]);
+        assert_eq!(cli.output, OutputFormat::Json);
+        assert_eq!(Cli::parse_from(["1i", "backends"]).output, OutputFormat::Table);
+        assert!(Cli::try_parse_from(["1i", "backends", "--output", "xml"]).is_err());
+    }
+
+    #[test]
+    fn test_sync_manifest_path() {
+        let cli = Cli::parse_from(["1i", "sync", "--manifest", "tools/1install.toml"]);
+        match cli.command {
+            Commands::Sync { manifest, locked } => {
+                assert_eq!(manifest, Some(PathBuf::from("tools/1install.toml")));
+                assert!(!locked);
+            }
+            _ => panic!("Expected Sync command"),
+        }
+    }
+
+    #[test]
+    fn test_undo_optional_id() {
+        match Cli::parse_from(["1i", "undo"]).command {
+            Commands::Undo { id } => assert_eq!(id, None),
+            _ => panic!("Expected Undo command"),
+        }
+        match Cli::parse_from(["1i", "undo", "12"]).command {
+            Commands::Undo { id } => assert_eq!(id, Some(12)),
+            _ => panic!("Expected Undo command"),
+        }
+        assert!(Cli::try_parse_from(["1i", "undo", "last"]).is_err());
+    }
+
+    #[test]
+    fn test_install_multiple_packages() {
+        let cli = Cli::parse_from(["1i", "install", "jq", "ripgrep", "fd", "--atomic"]);
+        match cli.command {
+            Commands::Install { packages, atomic, .. } => {
+                let names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+                assert_eq!(names, vec!["jq", "ripgrep", "fd"]);
+                assert!(atomic);
+            }
+            _ => panic!("Expected Install command"),
+        }
+        assert!(Cli::try_parse_from(["1i", "install"]).is_err());
+        
+        match Cli::parse_from(["1i", "install", "jq", "--no-fallback"]).command {
+            Commands::Install { no_fallback, atomic, .. } => assert!(no_fallback && !atomic),
+            _ => panic!("Expected Install command"),
+        }
+        
+        match Cli::parse_from(["1i", "install", "httpie", "--prefer", "newest"]).command {
+            Commands::Install { smart, prefer, .. } => {
+                assert!(!smart);
+                assert_eq!(prefer, Some(InstallPolicy::Newest));
+            }
+            _ => panic!("Expected Install command"),
+        }
+        assert!(Cli::try_parse_from(["1i", "install", "jq", "--prefer", "fastest"]).is_err());
+    }
+
+    #[test]
+    fn test_package_specs() {
+        let cli = Cli::parse_from(["1i", "install", "npm:prettier", "pip:black@23.1"]);
+        match cli.command {
+            Commands::Install { packages, .. } => {
+                assert_eq!(packages[0].backend.as_deref(), Some("npm"));
+                assert_eq!(packages[1].to_string(), "pip:black@23.1");
+            }
+            _ => panic!("Expected Install command"),
+        }
+        assert!(Cli::try_parse_from(["1i", "uninstall", "jq@"]).is_err());
+    }
+
+    #[test]
+    fn test_global_dry_run() {
+        let cli = Cli::parse_from(["1i", "install", "jq", "--dry-run"]);
+        assert!(cli.dry_run);
+    }
 }
@@ -0,0 +1,150 @@
+//! Machine-readable output formats selected with `--output`
+
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::RwLock;
+use crate::error::OneInstallError;
+
+/// How command results are printed
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable tables and messages
+    #[default]
+    Table,
+    /// Pretty-printed JSON
+    Json,
+    /// YAML
+    Yaml,
+    /// CSV with a header row; nested fields use dotted column names
+    Csv,
+}
+
+static FORMAT: RwLock<OutputFormat> = RwLock::new(OutputFormat::Table);
+
+/// Select the process-wide output format
+pub fn set_output_format(format: OutputFormat) {
+    if let Ok(mut slot) = FORMAT.write() {
+        *slot = format;
+    }
+}
+
+/// The process-wide output format
+pub fn output_format() -> OutputFormat {
+    FORMAT.read().map(|f| *f).unwrap_or_default()
+}
+
+/// Whether human-readable progress and tables should be printed
+pub fn is_human_output() -> bool {
+    output_format() == OutputFormat::Table
+}
+
+/// Print a list of records in the selected machine format
+pub fn emit_list<T: Serialize>(items: &[T]) -> Result<(), OneInstallError> {
+    match output_format() {
+        OutputFormat::Csv => {
+            let rows = items.iter()
+                .map(|item| serde_json::to_value(item).map(|v| flatten(&v)))
+                .collect::<Result<Vec<_>, _>>()?;
+            print!("{}", to_csv(&rows)?);
+            Ok(())
+        }
+        _ => emit_one(&items),
+    }
+}
+
+/// Print a single record in the selected machine format
+pub fn emit_one<T: Serialize + ?Sized>(value: &T) -> Result<(), OneInstallError> {
+    match output_format() {
+        OutputFormat::Json | OutputFormat::Table => {
+            println!("{}", serde_json::to_string_pretty(value)?);
+        }
+        OutputFormat::Yaml => {
+            let yaml = serde_yaml::to_string(value).map_err(|e| OneInstallError::Parse(e.to_string()))?;
+            print!("{}", yaml);
+        }
+        OutputFormat::Csv => {
+            let row = flatten(&serde_json::to_value(value)?);
+            print!("{}", to_csv(&[row])?);
+        }
+    }
+    Ok(())
+}
+
+/// Flatten a JSON value into `(column, cell)` pairs
+///
+/// Objects become dotted column names, lists of scalars are joined with `;`
+/// and anything more deeply nested is embedded as JSON.
+fn flatten(value: &Value) -> Vec<(String, String)> {
+    fn walk(prefix: &str, value: &Value, out: &mut Vec<(String, String)>) {
+        match value {
+            Value::Object(map) => {
+                for (key, child) in map {
+                    let column = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                    walk(&column, child, out);
+                }
+            }
+            other => out.push((prefix.to_string(), cell(other))),
+        }
+    }
+
+    let mut out = Vec::new();
+    let prefix = if value.is_object() { "" } else { "value" };
+    walk(prefix, value, &mut out);
+    out
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Array(items) if items.iter().all(|v| !v.is_object() && !v.is_array()) => {
+            items.iter().map(cell).collect::<Vec<_>>().join(";")
+        }
+        other => other.to_string(),
+    }
+}
+
+fn to_csv(rows: &[Vec<(String, String)>]) -> Result<String, OneInstallError> {
+    let mut header: Vec<&str> = Vec::new();
+    for (column, _) in rows.iter().flatten() {
+        if !header.contains(&column.as_str()) {
+            header.push(column);
+        }
+    }
+    if header.is_empty() {
+        return Ok(String::new());
+    }
+
+    let csv_error = |e: csv::Error| OneInstallError::Parse(e.to_string());
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&header).map_err(csv_error)?;
+    for row in rows {
+        let record = header.iter().map(|column| {
+            row.iter().find(|(c, _)| c == column).map(|(_, v)| v.as_str()).unwrap_or("")
+        });
+        writer.write_record(record).map_err(csv_error)?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| OneInstallError::Parse(e.to_string()))?;
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_csv_flattens_nested_fields() {
+        let rows = vec![
+            flatten(&json!({"name": "jq", "version": null, "deps": ["a", "b"], "meta": {"size": 3}})),
+            flatten(&json!({"name": "fd, the finder", "version": "9.0"})),
+        ];
+
+        assert_eq!(
+            to_csv(&rows).unwrap(),
+            "deps,meta.size,name,version\na;b,3,jq,\n,,\"fd, the finder\",9.0\n"
+        );
+    }
+}
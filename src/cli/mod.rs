@@ -1,7 +1,16 @@
 //! CLI module - command line interface definitions
 
 mod commands;
+mod format;
 mod output;
+mod prompt;
 
 pub use commands::{Cli, Commands, ConfigAction, ShimsAction};
-pub use output::{render_search_results, render_backends};
+pub use format::{OutputFormat, set_output_format, output_format, is_human_output, emit_list, emit_one};
+pub use output::{
+    render_search_results, render_backends, render_command_plan, render_installed_packages,
+    render_outdated_packages, render_upgrade_summary, render_package_info,
+    render_search_summary, render_streamed_results, render_search_progress, clear_search_progress,
+    render_sync_plan, render_history, render_install_summary, render_backend_choice, render_shim_changes,
+};
+pub use prompt::{can_prompt, prompt_choice, confirm};
@@ -1,8 +1,15 @@
 //! CLI output formatting
 
-use crate::search::PackageResult;
+use crate::backends::{BackendCommand, BackendKind, OutdatedPackage, PackageInfo};
+use crate::error::OneInstallError;
+use crate::manifest::{SyncAction, SyncStep};
+use crate::state::{JournalAction, JournalEntry, StateDb};
+use crate::{InstallOutcome, InstallStatus};
+use crate::search::{BackendChoice, BackendSearchReport, PackageResult, SearchOutcome};
+use crate::shims::{ShimChange, ShimChangeKind};
 use comfy_table::{Table, Row, Cell, Color, Attribute};
 use comfy_table::presets::UTF8_FULL;
+use std::io::IsTerminal;
 
 /// Render search results as a formatted table
 pub fn render_search_results(results: &[PackageResult], limit: usize) {
@@ -56,6 +63,436 @@ pub fn render_search_results(results: &[PackageResult], limit: usize) {
     }
 }
 
+/// Render one backend's results as soon as its search finishes
+pub fn render_streamed_results(report: &BackendSearchReport, results: &[PackageResult], limit: usize) {
+    let status = match &report.outcome {
+        SearchOutcome::Completed(count) if report.cached => format!("{} cached results", count),
+        SearchOutcome::Completed(count) => format!("{} results", count),
+        SearchOutcome::Failed(e) => format!("failed: {}", e.lines().next().unwrap_or_default()),
+        SearchOutcome::TimedOut => "timed out".to_string(),
+    };
+    println!("── {} ({}, {:.1}s)", report.backend, status, report.elapsed.as_secs_f32());
+    
+    for result in results.iter().take(limit) {
+        println!(
+            "   {} {}  {}",
+            result.name,
+            result.version.as_deref().unwrap_or("-"),
+            result.description.as_deref().unwrap_or(""),
+        );
+    }
+    if results.len() > limit {
+        println!("   … {} more", results.len() - limit);
+    }
+}
+
+/// Show which backends a streaming search is still waiting on
+///
+/// On a terminal the line is redrawn in place on stderr; call
+/// [`clear_search_progress`] before printing anything else.
+pub fn render_search_progress(pending: &[String]) {
+    if pending.is_empty() {
+        return;
+    }
+    let line = format!("   ⏳ Waiting on {}: {}", pending.len(), pending.join(", "));
+    if std::io::stderr().is_terminal() {
+        eprint!("\r\x1b[2K{}", line);
+    } else {
+        eprintln!("{}", line);
+    }
+}
+
+/// Erase the in-place progress line drawn by [`render_search_progress`]
+pub fn clear_search_progress() {
+    if std::io::stderr().is_terminal() {
+        eprint!("\r\x1b[2K");
+    }
+}
+
+/// Render installed packages merged across backends
+///
+/// Packages installed through 1install under another name show it too.
+pub fn render_installed_packages(packages: &[PackageResult], state: &StateDb) {
+    if packages.is_empty() {
+        println!("No installed packages found.");
+        return;
+    }
+    
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    
+    table.set_header(vec![
+        Cell::new("Package").add_attribute(Attribute::Bold),
+        Cell::new("Version").add_attribute(Attribute::Bold),
+        Cell::new("Backend").add_attribute(Attribute::Bold),
+    ]);
+    
+    for package in packages {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(package_label(&package.name, &package.source, state)));
+        row.add_cell(Cell::new(package.version.as_deref().unwrap_or("-")));
+        row.add_cell(Cell::new(&package.source).fg(source_color(&package.source)));
+        table.add_row(row);
+    }
+    
+    println!("{table}");
+    println!("\n{} packages installed.", packages.len());
+}
+
+/// Render packages that have newer versions available
+pub fn render_outdated_packages(packages: &[OutdatedPackage]) {
+    if packages.is_empty() {
+        println!("✓ Everything is up to date.");
+        return;
+    }
+    
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    
+    table.set_header(vec![
+        Cell::new("Package").add_attribute(Attribute::Bold),
+        Cell::new("Installed").add_attribute(Attribute::Bold),
+        Cell::new("Latest").add_attribute(Attribute::Bold),
+        Cell::new("Backend").add_attribute(Attribute::Bold),
+    ]);
+    
+    for package in packages {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(&package.name));
+        row.add_cell(Cell::new(package.current.as_deref().unwrap_or("-")));
+        row.add_cell(Cell::new(package.latest.as_deref().unwrap_or("-")).fg(Color::Green));
+        row.add_cell(Cell::new(&package.source).fg(source_color(&package.source)));
+        table.add_row(row);
+    }
+    
+    println!("{table}");
+    println!("\n{} packages can be upgraded. Run '1i upgrade --all' to upgrade them.", packages.len());
+}
+
+/// Render the per-backend outcome of `1i upgrade --all`
+pub fn render_upgrade_summary(outcomes: &[(String, Result<(), OneInstallError>)]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    
+    table.set_header(vec![
+        Cell::new("Backend").add_attribute(Attribute::Bold),
+        Cell::new("Status").add_attribute(Attribute::Bold),
+        Cell::new("Details").add_attribute(Attribute::Bold),
+    ]);
+    
+    for (backend, outcome) in outcomes {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(backend).fg(source_color(backend)));
+        match outcome {
+            Ok(()) => {
+                row.add_cell(Cell::new("✓ Upgraded").fg(Color::Green));
+                row.add_cell(Cell::new("-"));
+            }
+            Err(OneInstallError::Unsupported(reason)) => {
+                row.add_cell(Cell::new("- Unsupported").fg(Color::Yellow));
+                row.add_cell(Cell::new(reason));
+            }
+            Err(e) => {
+                row.add_cell(Cell::new("✗ Failed").fg(Color::Red));
+                row.add_cell(Cell::new(e));
+            }
+        }
+        table.add_row(row);
+    }
+    
+    println!("{table}");
+}
+
+/// Render the per-package results of a multi-package install
+pub fn render_install_summary(outcomes: &[InstallOutcome]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    
+    table.set_header(vec![
+        Cell::new("Package").add_attribute(Attribute::Bold),
+        Cell::new("Backend").add_attribute(Attribute::Bold),
+        Cell::new("Status").add_attribute(Attribute::Bold),
+        Cell::new("Details").add_attribute(Attribute::Bold),
+    ]);
+    
+    for outcome in outcomes {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(&outcome.package));
+        row.add_cell(Cell::new(&outcome.backend).fg(source_color(&outcome.backend)));
+        match &outcome.status {
+            InstallStatus::Installed => {
+                row.add_cell(Cell::new("✓ Installed").fg(Color::Green));
+                row.add_cell(Cell::new("-"));
+            }
+            InstallStatus::Planned => {
+                row.add_cell(Cell::new("○ Would install").fg(Color::Cyan));
+                row.add_cell(Cell::new("dry run"));
+            }
+            InstallStatus::Failed(reason) => {
+                row.add_cell(Cell::new("✗ Failed").fg(Color::Red));
+                row.add_cell(Cell::new(reason.lines().next().unwrap_or_default()));
+            }
+            InstallStatus::RolledBack(reason) => {
+                row.add_cell(Cell::new("↩ Rolled back").fg(Color::Yellow));
+                row.add_cell(Cell::new(reason.lines().next().unwrap_or_default()));
+            }
+        }
+        table.add_row(row);
+    }
+    
+    println!("{table}");
+}
+
+/// Render the backends smart install considered, the chosen one first
+pub fn render_backend_choice(choice: &BackendChoice) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    
+    table.set_header(vec![
+        Cell::new("#").add_attribute(Attribute::Bold),
+        Cell::new("Backend").add_attribute(Attribute::Bold),
+        Cell::new("Version").add_attribute(Attribute::Bold),
+        Cell::new("Kind").add_attribute(Attribute::Bold),
+        Cell::new("Description").add_attribute(Attribute::Bold),
+    ]);
+    
+    for (index, candidate) in choice.candidates.iter().enumerate() {
+        let mut row = Row::new();
+        let marker = if index == 0 { "1 ✓".to_string() } else { (index + 1).to_string() };
+        row.add_cell(Cell::new(marker));
+        row.add_cell(Cell::new(&candidate.source).fg(source_color(&candidate.source)));
+        row.add_cell(Cell::new(candidate.version.as_deref().unwrap_or("-")));
+        row.add_cell(Cell::new(BackendKind::of(&candidate.source)));
+        let desc = candidate.description.as_deref().unwrap_or("-");
+        let desc = if desc.chars().count() > 50 {
+            format!("{}...", desc.chars().take(47).collect::<String>())
+        } else {
+            desc.to_string()
+        };
+        row.add_cell(Cell::new(desc));
+        table.add_row(row);
+    }
+    
+    println!("{table}");
+}
+
+/// Render the shims a refresh regenerated, retargeted or removed
+pub fn render_shim_changes(changes: &[ShimChange]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    
+    table.set_header(vec![
+        Cell::new("Shim").add_attribute(Attribute::Bold),
+        Cell::new("Change").add_attribute(Attribute::Bold),
+        Cell::new("Target").add_attribute(Attribute::Bold),
+    ]);
+    
+    for change in changes {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(&change.name));
+        match change.change {
+            ShimChangeKind::Regenerated => {
+                row.add_cell(Cell::new("✓ Regenerated").fg(Color::Green));
+                row.add_cell(Cell::new(change.previous.display()));
+            }
+            ShimChangeKind::Retargeted => {
+                row.add_cell(Cell::new("↪ Retargeted").fg(Color::Yellow));
+                let target = change.target.as_deref().unwrap_or(&change.previous);
+                row.add_cell(Cell::new(format!("{} → {}", change.previous.display(), target.display())));
+            }
+            ShimChangeKind::Removed => {
+                row.add_cell(Cell::new("✗ Removed").fg(Color::Red));
+                row.add_cell(Cell::new(format!("{} (missing)", change.previous.display())));
+            }
+        }
+        table.add_row(row);
+    }
+    
+    println!("{table}");
+}
+
+/// Render how each backend's search ended (results, failures, timeouts)
+pub fn render_search_summary(reports: &[BackendSearchReport]) {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    
+    table.set_header(vec![
+        Cell::new("Backend").add_attribute(Attribute::Bold),
+        Cell::new("Status").add_attribute(Attribute::Bold),
+        Cell::new("Time").add_attribute(Attribute::Bold),
+    ]);
+    
+    for report in reports {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(&report.backend).fg(source_color(&report.backend)));
+        match &report.outcome {
+            SearchOutcome::Completed(count) => {
+                let cached = if report.cached { " (cached)" } else { "" };
+                row.add_cell(Cell::new(format!("✓ {} results{}", count, cached)).fg(Color::Green));
+            }
+            SearchOutcome::Failed(e) => {
+                let first_line = e.lines().next().unwrap_or_default();
+                row.add_cell(Cell::new(format!("✗ Failed: {}", first_line)).fg(Color::Red));
+            }
+            SearchOutcome::TimedOut => {
+                row.add_cell(Cell::new("⏱ Timed out").fg(Color::Yellow));
+            }
+        }
+        row.add_cell(Cell::new(format!("{:.1}s", report.elapsed.as_secs_f32())));
+        table.add_row(row);
+    }
+    
+    println!("{table}");
+}
+
+/// Render the plan computed by `1i sync`
+pub fn render_sync_plan(steps: &[SyncStep]) {
+    if steps.is_empty() {
+        println!("The manifest lists no packages for this system.");
+        return;
+    }
+    
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    
+    table.set_header(vec![
+        Cell::new("Package").add_attribute(Attribute::Bold),
+        Cell::new("Backend").add_attribute(Attribute::Bold),
+        Cell::new("Installed").add_attribute(Attribute::Bold),
+        Cell::new("Wanted").add_attribute(Attribute::Bold),
+        Cell::new("Action").add_attribute(Attribute::Bold),
+    ]);
+    
+    for step in steps {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(&step.package.name));
+        row.add_cell(Cell::new(&step.backend).fg(source_color(&step.backend)));
+        row.add_cell(Cell::new(step.installed.as_deref().unwrap_or("-")));
+        row.add_cell(Cell::new(step.package.version.as_deref().unwrap_or("any")));
+        row.add_cell(match step.action {
+            SyncAction::Install => Cell::new("install").fg(Color::Yellow),
+            SyncAction::Upgrade => Cell::new("upgrade").fg(Color::Yellow),
+            SyncAction::Satisfied => Cell::new("✓ ok").fg(Color::Green),
+        });
+        table.add_row(row);
+    }
+    
+    println!("{table}");
+}
+
+/// Render journal entries, oldest first
+///
+/// Undo links are resolved within `entries`, so pass a contiguous tail of
+/// the journal (optionally filtered to one package).
+pub fn render_history(entries: &[JournalEntry], state: &StateDb) {
+    if entries.is_empty() {
+        println!("No changes recorded yet.");
+        return;
+    }
+    
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    
+    table.set_header(vec![
+        Cell::new("ID").add_attribute(Attribute::Bold),
+        Cell::new("When").add_attribute(Attribute::Bold),
+        Cell::new("Action").add_attribute(Attribute::Bold),
+        Cell::new("Package").add_attribute(Attribute::Bold),
+        Cell::new("Backend").add_attribute(Attribute::Bold),
+        Cell::new("Version").add_attribute(Attribute::Bold),
+        Cell::new("Note").add_attribute(Attribute::Bold),
+    ]);
+    
+    for entry in entries {
+        let version = match (&entry.before, &entry.after) {
+            (None, None) => "-".to_string(),
+            (Some(before), None) => before.clone(),
+            (None, Some(after)) => after.clone(),
+            (Some(before), Some(after)) if before == after => after.clone(),
+            (Some(before), Some(after)) => format!("{} → {}", before, after),
+        };
+        let note = match (entry.undoes, entries.iter().find(|e| e.undoes == Some(entry.id))) {
+            (Some(original), _) => Cell::new(format!("undo of #{}", original)).fg(Color::Cyan),
+            (None, Some(undo)) => Cell::new(format!("undone by #{}", undo.id)).fg(Color::DarkGrey),
+            (None, None) => Cell::new(""),
+        };
+        
+        let mut row = Row::new();
+        row.add_cell(Cell::new(entry.id));
+        row.add_cell(Cell::new(entry.timestamp.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M")));
+        row.add_cell(match entry.action {
+            JournalAction::Install => Cell::new("install").fg(Color::Green),
+            JournalAction::Update => Cell::new("update").fg(Color::Yellow),
+            JournalAction::Uninstall => Cell::new("uninstall").fg(Color::Red),
+        });
+        row.add_cell(Cell::new(package_label(&entry.package, &entry.backend, state)));
+        row.add_cell(Cell::new(&entry.backend).fg(source_color(&entry.backend)));
+        row.add_cell(Cell::new(version));
+        row.add_cell(note);
+        table.add_row(row);
+    }
+    
+    println!("{table}");
+}
+
+/// A backend's package name, followed by the name the user asked for if different
+fn package_label(name: &str, backend: &str, state: &StateDb) -> String {
+    match state.requested_as(name, backend) {
+        Some(requested) => format!("{} ({})", name, requested),
+        None => name.to_string(),
+    }
+}
+
+/// Render package metadata with one column per backend
+pub fn render_package_info(package: &str, infos: &[PackageInfo]) {
+    if infos.is_empty() {
+        println!("No backend knows a package named '{}'.", package);
+        return;
+    }
+    
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL);
+    
+    let mut header = vec![Cell::new("").add_attribute(Attribute::Bold)];
+    header.extend(infos.iter().map(|info| {
+        Cell::new(&info.source).add_attribute(Attribute::Bold).fg(source_color(&info.source))
+    }));
+    table.set_header(header);
+    
+    let columns: Vec<Vec<(&str, String)>> = infos.iter().map(info_fields).collect();
+    for (row_index, (label, _)) in columns[0].iter().enumerate() {
+        let mut row = Row::new();
+        row.add_cell(Cell::new(label).add_attribute(Attribute::Bold));
+        for column in &columns {
+            row.add_cell(Cell::new(&column[row_index].1));
+        }
+        table.add_row(row);
+    }
+    
+    println!("{table}");
+}
+
+/// Labelled display values for each PackageInfo field
+fn info_fields(info: &PackageInfo) -> Vec<(&'static str, String)> {
+    let or_dash = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    let join_or_dash = |values: &[String]| {
+        if values.is_empty() { "-".to_string() } else { values.join(", ") }
+    };
+    
+    vec![
+        ("Name", info.name.clone()),
+        ("Description", or_dash(&info.description)),
+        ("Installed", or_dash(&info.installed_version)),
+        ("Candidate", or_dash(&info.candidate_version)),
+        ("Homepage", or_dash(&info.homepage)),
+        ("License", or_dash(&info.license)),
+        ("Size", or_dash(&info.size)),
+        ("Maintainer", or_dash(&info.maintainer)),
+        ("Dependencies", join_or_dash(&info.dependencies)),
+        ("Binaries", join_or_dash(&info.binaries)),
+    ]
+}
+
 /// Get color for a backend source
 fn source_color(source: &str) -> Color {
     match source {
@@ -71,7 +508,7 @@ fn source_color(source: &str) -> Color {
 
 /// Render list of available backends
 pub fn render_backends(backends: &[&str]) {
-    println!("Available backends on this system:\n");
+    println!("Available backends on this system (highest priority first):\n");
     for backend in backends {
         let _color = source_color(backend);
         // Using comfy_table colors for terminal output is slightly more complex, 
@@ -81,3 +518,16 @@ pub fn render_backends(backends: &[&str]) {
     }
     println!();
 }
+
+/// Render the commands recorded during a dry run
+pub fn render_command_plan(plan: &[BackendCommand]) {
+    println!("\n📋 Dry run: no changes were made.");
+    if plan.is_empty() {
+        println!("   No commands would be executed.");
+        return;
+    }
+    println!("   Planned commands ({}):", plan.len());
+    for (i, command) in plan.iter().enumerate() {
+        println!("   {}. {}", i + 1, command);
+    }
+}
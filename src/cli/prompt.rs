@@ -0,0 +1,80 @@
+//! Interactive prompts, shown only when someone is at the terminal
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use super::is_human_output;
+
+/// A parsed answer to a numbered-choice prompt
+#[derive(Debug, PartialEq)]
+enum Answer {
+    Pick(usize),
+    Quit,
+    Invalid,
+}
+
+/// Whether it makes sense to ask: stdin is a terminal, output is for people
+/// and `behavior.auto_confirm` is off
+pub fn can_prompt(auto_confirm: bool) -> bool {
+    !auto_confirm && is_human_output() && io::stdin().is_terminal()
+}
+
+/// Ask the user to pick one of `count` numbered options (shown 1-based)
+///
+/// Enter picks `default`. Returns `None` if the user quits with `q` or
+/// closes stdin.
+pub fn prompt_choice(question: &str, count: usize, default: usize) -> Option<usize> {
+    loop {
+        let answer = read_answer(&format!("{} [1-{}, Enter = {}, q = quit]: ", question, count, default + 1))?;
+        match parse_choice(&answer, count, default) {
+            Answer::Pick(index) => return Some(index),
+            Answer::Quit => return None,
+            Answer::Invalid => println!("   Please enter a number from 1 to {}.", count),
+        }
+    }
+}
+
+/// Ask a yes/no question; Enter (or closed stdin) gives `default`
+pub fn confirm(question: &str, default: bool) -> bool {
+    let hint = if default { "Y/n" } else { "y/N" };
+    match read_answer(&format!("{} [{}]: ", question, hint)).as_deref().map(str::trim) {
+        Some("y") | Some("Y") | Some("yes") => true,
+        Some("n") | Some("N") | Some("no") => false,
+        _ => default,
+    }
+}
+
+/// Print a prompt and read one line; `None` at end of input
+fn read_answer(prompt: &str) -> Option<String> {
+    print!("   {}", prompt);
+    io::stdout().flush().ok()?;
+    let mut line = String::new();
+    match io::stdin().lock().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line),
+    }
+}
+
+fn parse_choice(answer: &str, count: usize, default: usize) -> Answer {
+    match answer.trim() {
+        "" => Answer::Pick(default),
+        "q" | "Q" => Answer::Quit,
+        number => match number.parse::<usize>() {
+            Ok(n) if (1..=count).contains(&n) => Answer::Pick(n - 1),
+            _ => Answer::Invalid,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_choice() {
+        assert_eq!(parse_choice("\n", 3, 0), Answer::Pick(0));
+        assert_eq!(parse_choice(" 3 \n", 3, 0), Answer::Pick(2));
+        assert_eq!(parse_choice("q", 3, 0), Answer::Quit);
+        assert_eq!(parse_choice("4", 3, 0), Answer::Invalid);
+        assert_eq!(parse_choice("0", 3, 0), Answer::Invalid);
+        assert_eq!(parse_choice("apt", 3, 0), Answer::Invalid);
+    }
+}
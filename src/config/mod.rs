@@ -2,4 +2,4 @@
 
 mod settings;
 
-pub use settings::{Config, load_config, save_config, get_config_path};
+pub use settings::{Config, BackendConfig, InstallConfig, InstallPolicy, ShimMode, load_config, save_config, get_config_path, get_data_dir};
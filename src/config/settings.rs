@@ -1,11 +1,16 @@
 //! Configuration settings
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
 use std::fs;
 use std::path::PathBuf;
+use std::str::FromStr;
+use crate::backends::{is_backend_name, BACKEND_NAMES};
+use crate::error::OneInstallError;
 
 /// Main configuration structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
     /// Backend preferences
     pub backends: BackendConfig,
@@ -13,6 +18,12 @@ pub struct Config {
     pub behavior: BehaviorConfig,
     /// Shim settings
     pub shims: ShimConfig,
+    /// Search settings
+    #[serde(default)]
+    pub search: SearchConfig,
+    /// Install settings
+    #[serde(default)]
+    pub install: InstallConfig,
     /// Telemetry settings
     pub telemetry: TelemetryConfig,
 }
@@ -37,14 +48,115 @@ pub struct BehaviorConfig {
     pub auto_confirm: bool,
     /// Create shims for installed binaries
     pub create_shims: bool,
+    /// Try other backends when the chosen one cannot install a package
+    pub fallback: bool,
 }
 
 /// Shim configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct ShimConfig {
-    /// Automatically refresh shims after install
+    /// Refresh all shims after installs and updates
     pub auto_refresh: bool,
+    /// What a shim file is: a shell script or the compiled `1i-shim`
+    pub mode: ShimMode,
+}
+
+/// How shims are written to the shim directory
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShimMode {
+    /// A `#!/bin/sh` script (`.cmd`/`.ps1` on Windows) with the target baked in
+    #[default]
+    Script,
+    /// A hardlink to `1i-shim`, which looks its target up in the registry
+    Native,
+}
+
+impl fmt::Display for ShimMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ShimMode::Script => "script",
+            ShimMode::Native => "native",
+        })
+    }
+}
+
+impl FromStr for ShimMode {
+    type Err = OneInstallError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "script" => Ok(ShimMode::Script),
+            "native" => Ok(ShimMode::Native),
+            _ => Err(OneInstallError::Config(format!(
+                "Invalid shim mode: '{}' (expected script or native)", value
+            ))),
+        }
+    }
+}
+
+/// Search configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    /// Seconds each backend may spend on a search before it is killed
+    pub timeout_secs: u64,
+    /// Seconds cached search results stay fresh (0 disables the cache)
+    pub cache_ttl_secs: u64,
+    /// Seconds cached results are kept for `search --offline` after going stale
+    pub cache_retention_secs: u64,
+}
+
+/// Install configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstallConfig {
+    /// Search every backend to choose one when none is named
+    pub smart: bool,
+    /// Which backend smart install prefers when several have the package
+    pub policy: InstallPolicy,
+    /// Backend to use for a package when none is named (package -> backend)
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub choices: BTreeMap<String, String>,
+}
+
+/// Tie-breaking rule for smart install
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallPolicy {
+    /// OS package managers first, then universal ones, then language ecosystems
+    #[default]
+    System,
+    /// Language package managers (npm, pip, cargo, go) first
+    Native,
+    /// Whichever backend offers the highest version
+    Newest,
+}
+
+impl fmt::Display for InstallPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            InstallPolicy::System => "system",
+            InstallPolicy::Native => "native",
+            InstallPolicy::Newest => "newest",
+        })
+    }
+}
+
+impl FromStr for InstallPolicy {
+    type Err = OneInstallError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "system" => Ok(InstallPolicy::System),
+            "native" => Ok(InstallPolicy::Native),
+            "newest" => Ok(InstallPolicy::Newest),
+            _ => Err(OneInstallError::Config(format!(
+                "Invalid install policy: '{}' (expected system, native or newest)", value
+            ))),
+        }
+    }
 }
 
 impl Default for BehaviorConfig {
@@ -53,6 +165,7 @@ impl Default for BehaviorConfig {
             verbose: false,
             auto_confirm: true,
             create_shims: true,
+            fallback: true,
         }
     }
 }
@@ -61,17 +174,17 @@ impl Default for ShimConfig {
     fn default() -> Self {
         Self {
             auto_refresh: true,
+            mode: ShimMode::Script,
         }
     }
 }
 
-impl Default for Config {
+impl Default for SearchConfig {
     fn default() -> Self {
         Self {
-            backends: BackendConfig::default(),
-            behavior: BehaviorConfig::default(),
-            shims: ShimConfig::default(),
-            telemetry: TelemetryConfig::default(),
+            timeout_secs: 10,
+            cache_ttl_secs: 3600,
+            cache_retention_secs: 30 * 24 * 3600,
         }
     }
 }
@@ -123,8 +236,14 @@ pub fn get_config_path() -> PathBuf {
     }
 }
 
+/// Get the data directory (shims, registry, caches)
+pub fn get_data_dir() -> PathBuf {
+    let home = dirs::home_dir().expect("Could not find home directory");
+    home.join(".local").join("share").join("1install")
+}
+
 /// Load configuration from disk
-pub fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
+pub fn load_config() -> Result<Config, OneInstallError> {
     let path = get_config_path();
     
     if path.exists() {
@@ -137,7 +256,7 @@ pub fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
 }
 
 /// Save configuration to disk
-pub fn save_config(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+pub fn save_config(config: &Config) -> Result<(), OneInstallError> {
     let path = get_config_path();
     
     // Ensure parent directory exists
@@ -150,16 +269,51 @@ pub fn save_config(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Key prefix for per-package backend choices (`install.choices.<package>`)
+const CHOICES_PREFIX: &str = "install.choices.";
+
+fn invalid_bool(key: &str, value: &str) -> OneInstallError {
+    OneInstallError::Config(format!("Invalid boolean for {}: '{}'", key, value))
+}
+
+/// Parse a comma-separated list of backend names, rejecting unknown ones
+fn backend_list(key: &str, value: &str) -> Result<Vec<String>, OneInstallError> {
+    let names: Vec<String> = value.split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+    match names.iter().find(|name| !is_backend_name(name)) {
+        Some(unknown) => Err(OneInstallError::Config(format!(
+            "Unknown backend for {}: '{}' (expected one of {})", key, unknown, BACKEND_NAMES.join(", ")
+        ))),
+        None => Ok(names),
+    }
+}
+
+fn invalid_seconds(key: &str, value: &str) -> OneInstallError {
+    OneInstallError::Config(format!("Invalid number of seconds for {}: '{}'", key, value))
+}
+
 impl Config {
     /// Get a config value by dot-notation path
     pub fn get(&self, key: &str) -> Option<String> {
+        if let Some(package) = key.strip_prefix(CHOICES_PREFIX) {
+            return self.install.choices.get(package).cloned();
+        }
         match key {
             "backends.priority" => Some(self.backends.priority.join(",")),
             "backends.disabled" => Some(self.backends.disabled.join(",")),
             "behavior.verbose" => Some(self.behavior.verbose.to_string()),
             "behavior.auto_confirm" => Some(self.behavior.auto_confirm.to_string()),
             "behavior.create_shims" => Some(self.behavior.create_shims.to_string()),
+            "behavior.fallback" => Some(self.behavior.fallback.to_string()),
             "shims.auto_refresh" => Some(self.shims.auto_refresh.to_string()),
+            "shims.mode" => Some(self.shims.mode.to_string()),
+            "search.timeout_secs" => Some(self.search.timeout_secs.to_string()),
+            "search.cache_ttl_secs" => Some(self.search.cache_ttl_secs.to_string()),
+            "search.cache_retention_secs" => Some(self.search.cache_retention_secs.to_string()),
+            "install.smart" => Some(self.install.smart.to_string()),
+            "install.policy" => Some(self.install.policy.to_string()),
             "telemetry.enabled" => Some(self.telemetry.enabled.to_string()),
             "telemetry.client_id" => self.telemetry.client_id.clone(),
             _ => None,
@@ -167,44 +321,87 @@ impl Config {
     }
     
     /// Set a config value by dot-notation path
-    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+    ///
+    /// `install.choices.<package>` set to an empty value forgets the choice.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), OneInstallError> {
+        if let Some(package) = key.strip_prefix(CHOICES_PREFIX).filter(|p| !p.is_empty()) {
+            match backend_list(key, value)?.as_slice() {
+                [] => self.install.choices.remove(package),
+                [backend] => self.install.choices.insert(package.to_string(), backend.clone()),
+                _ => return Err(OneInstallError::Config(format!("{} takes a single backend", key))),
+            };
+            return Ok(());
+        }
         match key {
             "backends.priority" => {
-                self.backends.priority = value.split(',').map(|s| s.trim().to_string()).collect();
+                self.backends.priority = backend_list(key, value)?;
                 Ok(())
             }
             "backends.disabled" => {
-                self.backends.disabled = value.split(',').map(|s| s.trim().to_string()).collect();
+                self.backends.disabled = backend_list(key, value)?;
                 Ok(())
             }
             "behavior.verbose" => {
-                self.behavior.verbose = value.parse().map_err(|_| "Invalid boolean")?;
+                self.behavior.verbose = value.parse().map_err(|_| invalid_bool(key, value))?;
                 Ok(())
             }
             "behavior.auto_confirm" => {
-                self.behavior.auto_confirm = value.parse().map_err(|_| "Invalid boolean")?;
+                self.behavior.auto_confirm = value.parse().map_err(|_| invalid_bool(key, value))?;
                 Ok(())
             }
             "behavior.create_shims" => {
-                self.behavior.create_shims = value.parse().map_err(|_| "Invalid boolean")?;
+                self.behavior.create_shims = value.parse().map_err(|_| invalid_bool(key, value))?;
+                Ok(())
+            }
+            "behavior.fallback" => {
+                self.behavior.fallback = value.parse().map_err(|_| invalid_bool(key, value))?;
                 Ok(())
             }
             "shims.auto_refresh" => {
-                self.shims.auto_refresh = value.parse().map_err(|_| "Invalid boolean")?;
+                self.shims.auto_refresh = value.parse().map_err(|_| invalid_bool(key, value))?;
+                Ok(())
+            }
+            "shims.mode" => {
+                self.shims.mode = value.parse()?;
+                Ok(())
+            }
+            "search.timeout_secs" => {
+                self.search.timeout_secs = value.parse().map_err(|_| invalid_seconds(key, value))?;
+                Ok(())
+            }
+            "search.cache_ttl_secs" => {
+                self.search.cache_ttl_secs = value.parse().map_err(|_| invalid_seconds(key, value))?;
+                Ok(())
+            }
+            "search.cache_retention_secs" => {
+                self.search.cache_retention_secs = value.parse().map_err(|_| invalid_seconds(key, value))?;
+                Ok(())
+            }
+            "install.smart" => {
+                self.install.smart = value.parse().map_err(|_| invalid_bool(key, value))?;
+                Ok(())
+            }
+            "install.policy" => {
+                self.install.policy = value.parse()?;
                 Ok(())
             }
             "telemetry.enabled" => {
-                self.telemetry.enabled = value.parse().map_err(|_| "Invalid boolean")?;
+                self.telemetry.enabled = value.parse().map_err(|_| invalid_bool(key, value))?;
                 Ok(())
             }
             "telemetry.client_id" => {
                 self.telemetry.client_id = Some(value.to_string());
                 Ok(())
             }
-            _ => Err(format!("Unknown config key: {}", key)),
+            _ => Err(OneInstallError::Config(format!("Unknown config key: {}", key))),
         }
     }
     
+    /// Keys of the remembered per-package backend choices
+    pub fn choice_keys(&self) -> Vec<String> {
+        self.install.choices.keys().map(|package| format!("{}{}", CHOICES_PREFIX, package)).collect()
+    }
+    
     /// List all config keys
     pub fn list_keys() -> Vec<&'static str> {
         vec![
@@ -213,7 +410,14 @@ impl Config {
             "behavior.verbose",
             "behavior.auto_confirm",
             "behavior.create_shims",
+            "behavior.fallback",
             "shims.auto_refresh",
+            "shims.mode",
+            "search.timeout_secs",
+            "search.cache_ttl_secs",
+            "search.cache_retention_secs",
+            "install.smart",
+            "install.policy",
             "telemetry.enabled",
             "telemetry.client_id",
         ]
@@ -231,10 +435,75 @@ mod tests {
         assert!(config.behavior.auto_confirm);
     }
     
+    #[test]
+    fn test_load_config_without_newer_tables() {
+        // A config.toml saved before the search and install tables existed
+        let config: Config = toml::from_str(r#"
+            [backends]
+            priority = ["brew", "apt"]
+            disabled = ["snap"]
+
+            [behavior]
+            verbose = true
+
+            [shims]
+            auto_refresh = false
+
+            [telemetry]
+            enabled = false
+        "#).unwrap();
+        assert_eq!(config.backends.priority, vec!["brew", "apt"]);
+        assert_eq!(config.backends.disabled, vec!["snap"]);
+        assert_eq!(config.search.timeout_secs, SearchConfig::default().timeout_secs);
+    }
+    
     #[test]
     fn test_get_set() {
         let mut config = Config::default();
         config.set("behavior.verbose", "true").unwrap();
         assert_eq!(config.get("behavior.verbose"), Some("true".to_string()));
     }
+    
+    #[test]
+    fn test_set_backend_lists() {
+        let mut config = Config::default();
+        config.set("backends.priority", "brew, apt").unwrap();
+        assert_eq!(config.backends.priority, vec!["brew", "apt"]);
+        
+        config.set("backends.disabled", "").unwrap();
+        assert!(config.backends.disabled.is_empty());
+        
+        assert!(config.set("backends.disabled", "pipx").is_ok());
+        assert!(config.set("backends.disabled", "snap,nmp").is_err());
+    }
+    
+    #[test]
+    fn test_set_install_policy() {
+        let mut config = Config::default();
+        assert_eq!(config.get("install.policy"), Some("system".to_string()));
+        config.set("install.policy", "newest").unwrap();
+        assert_eq!(config.install.policy, InstallPolicy::Newest);
+        assert!(config.set("install.policy", "fastest").is_err());
+    }
+    
+    #[test]
+    fn test_set_shim_mode() {
+        let mut config = Config::default();
+        assert_eq!(config.get("shims.mode"), Some("script".to_string()));
+        config.set("shims.mode", "native").unwrap();
+        assert_eq!(config.shims.mode, ShimMode::Native);
+        assert!(config.set("shims.mode", "symlink").is_err());
+    }
+    
+    #[test]
+    fn test_install_choices() {
+        let mut config = Config::default();
+        config.set("install.choices.httpie", "snap").unwrap();
+        assert_eq!(config.get("install.choices.httpie"), Some("snap".to_string()));
+        assert_eq!(config.choice_keys(), vec!["install.choices.httpie"]);
+        assert!(config.set("install.choices.httpie", "apt,snap").is_err());
+        
+        config.set("install.choices.httpie", "").unwrap();
+        assert!(config.install.choices.is_empty());
+    }
 }
@@ -1,5 +1,6 @@
 //! Operating system detection
 
+#[cfg(any(target_os = "windows", target_os = "macos"))]
 use std::process::Command;
 
 /// Detected operating system type
@@ -15,6 +16,18 @@ pub enum OsType {
     Unknown,
 }
 
+impl OsType {
+    /// Short lowercase OS family name (`windows`, `linux`, `macos`, `unknown`)
+    pub fn family(&self) -> &'static str {
+        match self {
+            OsType::Windows => "windows",
+            OsType::Linux { .. } => "linux",
+            OsType::MacOS => "macos",
+            OsType::Unknown => "unknown",
+        }
+    }
+}
+
 /// Linux distribution identifiers
 #[derive(Debug, Clone, PartialEq)]
 pub enum LinuxDistro {
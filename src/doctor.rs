@@ -1,81 +1,150 @@
 //! System health and diagnostic tools
 
-use crate::backends::get_all_available_backends;
+use serde::Serialize;
+use std::path::PathBuf;
+use crate::backends::BackendResolver;
+use crate::cli::{emit_one, is_human_output};
 use crate::shims::{get_shim_dir, ShimRegistry};
+use crate::error::OneInstallError;
+
+/// A shim whose target binary no longer exists
+#[derive(Debug, Clone, Serialize)]
+pub struct BrokenShim {
+    pub name: String,
+    pub target: PathBuf,
+}
+
+/// A tool name that resolves to more than one binary on PATH
+#[derive(Debug, Clone, Serialize)]
+pub struct Conflict {
+    pub tool: String,
+    pub paths: Vec<String>,
+}
+
+/// Result of every diagnostic check
+#[derive(Debug, Clone, Serialize)]
+pub struct DoctorReport {
+    /// Shim directory location
+    pub shim_dir: PathBuf,
+    /// Whether the shim directory exists
+    pub shim_dir_exists: bool,
+    /// Whether the shim directory is on PATH
+    pub shim_dir_in_path: bool,
+    /// Number of registered shims
+    pub shim_count: usize,
+    /// Registered shims pointing at missing binaries
+    pub broken_shims: Vec<BrokenShim>,
+    /// Number of package managers available
+    pub backends_available: usize,
+    /// Binary name collisions on PATH
+    pub conflicts: Vec<Conflict>,
+}
 
 pub struct Doctor;
 
 impl Doctor {
-    pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+    pub fn run() -> Result<(), OneInstallError> {
+        if !is_human_output() {
+            return emit_one(&Self::diagnose()?);
+        }
+
         println!("🩺 Running 1install diagnostics...");
-        
-        Self::check_shim_dir()?;
-        Self::check_shim_registry()?;
-        Self::check_conflicts()?;
-        
+        let report = Self::diagnose()?;
+
+        Self::print_shim_dir(&report);
+        Self::print_shim_registry(&report);
+        Self::print_conflicts(&report);
+
         println!("\n✓ Diagnostics complete.");
         Ok(())
     }
 
-    fn check_shim_dir() -> Result<(), Box<dyn std::error::Error>> {
+    /// Run every check without printing anything
+    pub fn diagnose() -> Result<DoctorReport, OneInstallError> {
         let shim_dir = get_shim_dir();
+
+        // Check if in PATH
+        let path = std::env::var("PATH").unwrap_or_default();
+        let shim_dir_in_path = path.contains(shim_dir.to_str().unwrap_or(""));
+
+        let registry = ShimRegistry::load()?;
+        let mut broken_shims: Vec<BrokenShim> = registry.list()
+            .filter(|shim| !shim.target.exists())
+            .map(|shim| BrokenShim { name: shim.name.clone(), target: shim.target.clone() })
+            .collect();
+        broken_shims.sort_by(|a, b| a.name.cmp(&b.name));
+
+        let backends_available = BackendResolver::from_config().available().len();
+        let conflicts = if backends_available < 2 { Vec::new() } else { Self::find_conflicts() };
+
+        Ok(DoctorReport {
+            shim_dir_exists: shim_dir.exists(),
+            shim_dir,
+            shim_dir_in_path,
+            shim_count: registry.len(),
+            broken_shims,
+            backends_available,
+            conflicts,
+        })
+    }
+
+    fn print_shim_dir(report: &DoctorReport) {
         print!("   Shim directory... ");
-        
-        if shim_dir.exists() {
-            println!("✓ Exist ({})", shim_dir.display());
+
+        if report.shim_dir_exists {
+            println!("✓ Exist ({})", report.shim_dir.display());
         } else {
             println!("✗ Missing");
             println!("     Try running '1i self-install' to fix.");
         }
-        
-        // Check if in PATH
-        let path = std::env::var("PATH").unwrap_or_default();
-        let in_path = path.contains(shim_dir.to_str().unwrap_or(""));
-        
+
         print!("   PATH integration... ");
-        if in_path {
+        if report.shim_dir_in_path {
             println!("✓ Found");
         } else {
             println!("✗ Not found in $PATH");
         }
-        
-        Ok(())
     }
 
-    fn check_shim_registry() -> Result<(), Box<dyn std::error::Error>> {
-        let registry = ShimRegistry::load()?;
-        println!("   Shim registry... ✓ Loaded ({} shims)", registry.len());
-        
-        let mut broken = 0;
-        for shim in registry.list() {
-            if !shim.target.exists() {
-                broken += 1;
-                println!("     ✗ Broken shim: {} -> {} (target missing)", shim.name, shim.target.display());
-            }
+    fn print_shim_registry(report: &DoctorReport) {
+        println!("   Shim registry... ✓ Loaded ({} shims)", report.shim_count);
+
+        for shim in &report.broken_shims {
+            println!("     ✗ Broken shim: {} -> {} (target missing)", shim.name, shim.target.display());
         }
-        
-        if broken == 0 && !registry.is_empty() {
+
+        if report.broken_shims.is_empty() && report.shim_count > 0 {
             println!("     ✓ All shims point to valid targets.");
         }
-        
-        Ok(())
     }
 
-    fn check_conflicts() -> Result<(), Box<dyn std::error::Error>> {
+    fn print_conflicts(report: &DoctorReport) {
         println!("   Conflict detection...");
-        
-        let backends = get_all_available_backends();
-        if backends.len() < 2 {
+
+        if report.backends_available < 2 {
             println!("     ℹ Only one backend available, no conflicts possible.");
-            return Ok(());
+            return;
+        }
+
+        for conflict in &report.conflicts {
+            println!("     ⚠️ Found potential conflict for '{}':", conflict.tool);
+            for path in &conflict.paths {
+                println!("       - {}", path);
+            }
+        }
+
+        if report.conflicts.is_empty() {
+            println!("     ✓ No obvious binary name collisions detected.");
+        } else {
+            println!("\n     ℹ Recommendation: Use '1i shims refresh' or '1i install' with --backend to prioritize a specific version.");
         }
-        
+    }
+
+    fn find_conflicts() -> Vec<Conflict> {
         let common_tools = ["git", "node", "python", "python3", "jq", "rg", "fd", "npm", "pip", "docker", "curlie", "bat"];
-        let mut conflicts_found = 0;
+        let mut conflicts = Vec::new();
 
         for tool in common_tools {
-            let _providers: Vec<String> = Vec::new();
-            
             // Check via 'which' / 'where' to see if multiple paths exist
             // This is a proxy for detecting if different managers installed it to different locations
             #[cfg(windows)]
@@ -91,20 +160,10 @@ impl Doctor {
             };
 
             if paths.len() > 1 {
-                conflicts_found += 1;
-                println!("     ⚠️ Found potential conflict for '{}':", tool);
-                for path in paths {
-                    println!("       - {}", path);
-                }
+                conflicts.push(Conflict { tool: tool.to_string(), paths });
             }
         }
 
-        if conflicts_found == 0 {
-            println!("     ✓ No obvious binary name collisions detected.");
-        } else {
-            println!("\n     ℹ Recommendation: Use '1i shims refresh' or '1i install' with --backend to prioritize a specific version.");
-        }
-        
-        Ok(())
+        conflicts
     }
 }
@@ -0,0 +1,193 @@
+//! Crate-wide error type
+
+use crate::integrity::VerificationError;
+use std::io;
+use thiserror::Error;
+
+/// Every failure 1install can report, grouped by class
+///
+/// Each variant maps to a stable process exit code (see [`exit_code`](Self::exit_code))
+/// and a telemetry class name (see [`class`](Self::class)).
+#[derive(Error, Debug)]
+pub enum OneInstallError {
+    /// The request itself is malformed (e.g. git install without a URL)
+    #[error("{0}")]
+    InvalidInput(String),
+
+    /// A specific backend was requested but is not installed
+    #[error("Backend '{0}' not available")]
+    BackendNotAvailable(String),
+
+    /// No usable package manager could be found for this system
+    #[error("{0}")]
+    NoSupportedBackend(String),
+
+    /// The package does not exist in the backend(s) that were asked
+    #[error("Package '{package}' not found{}", .backend.as_ref().map(|b| format!(" in {}", b)).unwrap_or_default())]
+    PackageNotFound {
+        package: String,
+        backend: Option<String>,
+    },
+
+    /// The operation needs privileges the user does not have
+    #[error("Permission denied: {0}")]
+    PermissionDenied(String),
+
+    /// A native package manager command exited unsuccessfully
+    #[error("{command} failed with exit code: {code:?}{}", stderr_suffix(.stderr))]
+    CommandFailed {
+        command: String,
+        code: Option<i32>,
+        stderr: String,
+    },
+
+    /// Some items of a batch operation failed while others succeeded
+    #[error("{0}")]
+    PartialFailure(String),
+
+    /// Integrity verification of an installed binary failed
+    #[error("Security check failed: {0}")]
+    Integrity(#[from] VerificationError),
+
+    /// The backend cannot perform this operation
+    #[error("{0}")]
+    Unsupported(String),
+
+    /// Invalid configuration key or value
+    #[error("Configuration error: {0}")]
+    Config(String),
+
+    /// A data file or backend output could not be parsed or written
+    #[error("Parse error: {0}")]
+    Parse(String),
+
+    /// Filesystem or process I/O failure
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    /// Installed packages or the manifest disagree with the lockfile
+    #[error("Lockfile mismatch: {0}")]
+    LockMismatch(String),
+
+    /// A backend command was killed after exceeding its time budget
+    #[error("{command} timed out after {seconds}s")]
+    TimedOut {
+        command: String,
+        seconds: u64,
+    },
+
+    /// The user declined a prompt
+    #[error("Cancelled")]
+    Cancelled,
+}
+
+fn stderr_suffix(stderr: &str) -> String {
+    let stderr = stderr.trim();
+    if stderr.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", stderr)
+    }
+}
+
+impl OneInstallError {
+    /// Stable process exit code for this error class
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::InvalidInput(_) => 2,
+            Self::BackendNotAvailable(_) => 3,
+            Self::NoSupportedBackend(_) => 4,
+            Self::PackageNotFound { .. } => 5,
+            Self::PermissionDenied(_) => 6,
+            Self::CommandFailed { .. } => 7,
+            Self::PartialFailure(_) => 8,
+            Self::Integrity(_) => 9,
+            Self::Unsupported(_) => 10,
+            Self::Config(_) => 11,
+            Self::Parse(_) => 12,
+            Self::Io(_) => 13,
+            Self::TimedOut { .. } => 14,
+            Self::LockMismatch(_) => 15,
+            Self::Cancelled => 16,
+        }
+    }
+
+    /// Stable, anonymized class name used for telemetry
+    pub fn class(&self) -> &'static str {
+        match self {
+            Self::InvalidInput(_) => "invalid_input",
+            Self::BackendNotAvailable(_) => "backend_not_available",
+            Self::NoSupportedBackend(_) => "no_supported_backend",
+            Self::PackageNotFound { .. } => "package_not_found",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::CommandFailed { .. } => "command_failed",
+            Self::PartialFailure(_) => "partial_failure",
+            Self::Integrity(_) => "integrity",
+            Self::Unsupported(_) => "unsupported",
+            Self::Config(_) => "config",
+            Self::Parse(_) => "parse",
+            Self::Io(_) => "io",
+            Self::TimedOut { .. } => "timed_out",
+            Self::LockMismatch(_) => "lock_mismatch",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl From<toml::de::Error> for OneInstallError {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Parse(e.to_string())
+    }
+}
+
+impl From<toml::ser::Error> for OneInstallError {
+    fn from(e: toml::ser::Error) -> Self {
+        Self::Parse(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for OneInstallError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Parse(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors = [
+            OneInstallError::InvalidInput(String::new()),
+            OneInstallError::BackendNotAvailable("apt".into()),
+            OneInstallError::NoSupportedBackend(String::new()),
+            OneInstallError::PackageNotFound { package: "jq".into(), backend: None },
+            OneInstallError::PermissionDenied(String::new()),
+            OneInstallError::CommandFailed { command: "apt-get install".into(), code: Some(100), stderr: String::new() },
+            OneInstallError::PartialFailure(String::new()),
+            OneInstallError::Integrity(VerificationError::BinaryNotFound("rg".into())),
+            OneInstallError::Unsupported(String::new()),
+            OneInstallError::Config(String::new()),
+            OneInstallError::Parse(String::new()),
+            OneInstallError::Io(io::Error::other("disk")),
+            OneInstallError::TimedOut { command: String::new(), seconds: 0 },
+            OneInstallError::LockMismatch(String::new()),
+            OneInstallError::Cancelled,
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(|e| e.exit_code()).collect();
+        codes.sort();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+        assert!(!codes.contains(&0) && !codes.contains(&1));
+    }
+
+    #[test]
+    fn test_display() {
+        let err = OneInstallError::PackageNotFound { package: "jq".into(), backend: Some("apt".into()) };
+        assert_eq!(err.to_string(), "Package 'jq' not found in apt");
+
+        let err = OneInstallError::CommandFailed { command: "npm install".into(), code: Some(1), stderr: String::new() };
+        assert_eq!(err.to_string(), "npm install failed with exit code: Some(1)");
+    }
+}
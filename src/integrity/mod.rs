@@ -2,4 +2,4 @@
 
 mod verifier;
 
-pub use verifier::{verify_file_hash, VerificationError};
+pub use verifier::{compute_file_hash, verify_file_hash, VerificationError};
@@ -15,10 +15,12 @@ pub enum VerificationError {
         expected: String,
         actual: String,
     },
+    #[error("Could not locate binary for '{0}' to verify")]
+    BinaryNotFound(String),
 }
 
-/// Verify that a file's SHA-256 hash matches the expected hash
-pub fn verify_file_hash(path: &Path, expected_hash: &str) -> Result<(), VerificationError> {
+/// Compute the lowercase hex SHA-256 of a file
+pub fn compute_file_hash(path: &Path) -> Result<String, VerificationError> {
     let mut file = File::open(path)?;
     let mut hasher = Sha256::new();
     let mut buffer = [0u8; 8192];
@@ -31,8 +33,12 @@ pub fn verify_file_hash(path: &Path, expected_hash: &str) -> Result<(), Verifica
         hasher.update(&buffer[..count]);
     }
     
-    let result = hasher.finalize();
-    let actual_hash = hex::encode(result);
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Verify that a file's SHA-256 hash matches the expected hash
+pub fn verify_file_hash(path: &Path, expected_hash: &str) -> Result<(), VerificationError> {
+    let actual_hash = compute_file_hash(path)?;
     
     if actual_hash.to_lowercase() == expected_hash.to_lowercase() {
         Ok(())
@@ -43,3 +49,21 @@ pub fn verify_file_hash(path: &Path, expected_hash: &str) -> Result<(), Verifica
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compute_and_verify_hash() {
+        let path = std::env::temp_dir().join(format!("1install-hash-{}", std::process::id()));
+        std::fs::write(&path, b"abc").unwrap();
+
+        let hash = compute_file_hash(&path).unwrap();
+        assert_eq!(hash, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert!(verify_file_hash(&path, &hash.to_uppercase()).is_ok());
+        assert!(matches!(verify_file_hash(&path, "00"), Err(VerificationError::HashMismatch { .. })));
+
+        let _ = std::fs::remove_file(&path);
+    }
+}
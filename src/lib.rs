@@ -1,5 +1,6 @@
 //! 1install library - core functionality
 
+pub mod aliases;
 pub mod cli;
 pub mod context;
 pub mod backends;
@@ -9,76 +10,223 @@ pub mod config;
 pub mod integrity;
 pub mod telemetry;
 pub mod doctor;
+pub mod error;
+pub mod manifest;
+pub mod state;
 
-use cli::{Cli, Commands, ConfigAction, ShimsAction, render_search_results, render_backends};
+use cli::{
+    Cli, Commands, ConfigAction, ShimsAction, set_output_format, is_human_output, emit_list, emit_one,
+    render_search_results, render_backends, render_command_plan, render_installed_packages,
+    render_outdated_packages, render_upgrade_summary, render_package_info, render_search_summary,
+    render_streamed_results, render_search_progress, clear_search_progress, render_sync_plan,
+    render_history, render_install_summary, render_backend_choice, render_shim_changes, can_prompt,
+    prompt_choice, confirm,
+};
+use aliases::AliasDb;
 use context::OsContext;
-use backends::{get_backend_for_context, get_all_available_backends, Backend};
-use search::{SearchAggregator, PackageResult};
-use config::{load_config, save_config, get_config_path, Config};
-use shims::{get_shim_dir, ShimRegistry};
-use integrity::verify_file_hash;
+use backends::{
+    enable_dry_run, is_dry_run, with_timeout, capture_commands, prime_sudo, get_all_available_backends,
+    backend_matches, BackendResolver,
+    Backend, BackendCommand, OutdatedPackage, PackageInfo, PackageSpec, VersionReq,
+};
+use search::{SearchAggregator, PackageResult, SearchOutcome, BackendSearchReport, SearchCache, choose_backend};
+use config::{load_config, save_config, get_config_path, Config, InstallPolicy};
+use shims::{
+    get_shim_dir, refresh_shims, remove_shim, ShimChange, ShimChangeKind, ShimEntry, ShimRegistry, WorkingDir,
+};
+use integrity::{compute_file_hash, verify_file_hash, VerificationError};
 use telemetry::{TelemetryClient, TelemetryEvent};
 use doctor::Doctor;
+use state::{InstallRecord, StateDb, Journal, JournalAction, JournalEntry};
+use manifest::{
+    find_manifest, plan_sync, Manifest, ManifestPackage, SyncAction, SyncStep, Lockfile, LockedPackage,
+    MANIFEST_FILE, LOCK_FILE,
+};
+pub use error::OneInstallError;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
-use std::time::Instant;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Extra time given to a backend search beyond its configured timeout
+const SEARCH_TIMEOUT_SLACK: Duration = Duration::from_secs(2);
+
+/// Results shown per backend while a streaming search is still running
+const STREAMED_RESULTS_PER_BACKEND: usize = 5;
+
+/// How one package in a multi-package install ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
+pub enum InstallStatus {
+    Installed,
+    /// Would be installed; nothing ran (dry run)
+    Planned,
+    Failed(String),
+    RolledBack(String),
+}
+
+/// Per-package result of a multi-package install
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallOutcome {
+    pub package: String,
+    pub backend: String,
+    pub status: InstallStatus,
+}
 
 /// Transactional state for installation
-struct Transaction<'a> {
-    package: &'a str,
-    backend: Box<dyn Backend>,
+struct Transaction {
+    /// Package name as the backend knows it
+    package: String,
+    /// Package name as the user asked for it
+    requested: String,
+    backend: Arc<dyn Backend>,
     installed: bool,
-    shim_created: bool,
+    /// The package was already installed before this transaction
+    preexisting: bool,
+    /// Names of the shims this transaction created
+    shims: Vec<String>,
+    recorded: bool,
+    journal_id: Option<u64>,
 }
 
-impl<'a> Transaction<'a> {
-    fn new(package: &'a str, backend: Box<dyn Backend>) -> Self {
+impl Transaction {
+    fn new(package: impl Into<String>, backend: Arc<dyn Backend>) -> Self {
+        let package = package.into();
         Self {
+            requested: package.clone(),
             package,
             backend,
             installed: false,
-            shim_created: false,
+            preexisting: false,
+            shims: Vec::new(),
+            recorded: false,
+            journal_id: None,
         }
     }
 
-    fn rollback(&self) -> Result<(), Box<dyn std::error::Error>> {
-        if self.installed {
+    fn rollback(&self) -> Result<(), OneInstallError> {
+        if self.installed && !self.preexisting {
             println!("   ⚠️ Rolling back installation of {}...", self.package);
-            let _ = self.backend.uninstall(self.package);
+            let _ = self.backend.uninstall(&self.package);
         }
-        if self.shim_created {
+        if !self.shims.is_empty() {
             let mut registry = ShimRegistry::load()?;
-            if registry.remove(self.package).is_some() {
-                let _ = registry.save();
-                let shim_dir = get_shim_dir();
-                #[cfg(windows)]
-                {
-                    let _ = std::fs::remove_file(shim_dir.join(format!("{}.cmd", self.package)));
-                    let _ = std::fs::remove_file(shim_dir.join(format!("{}.ps1", self.package)));
-                }
-                #[cfg(not(windows))]
-                {
-                    let _ = std::fs::remove_file(shim_dir.join(self.package));
+            for shim in &self.shims {
+                if registry.remove(shim).is_some() {
+                    remove_shim(shim);
                 }
             }
+            let _ = registry.save();
+        }
+        if self.recorded {
+            let mut state = StateDb::load()?;
+            if state.remove(&self.package, self.backend.name()).is_some() {
+                state.save()?;
+            }
+        }
+        if self.installed && self.journal_id.is_some() {
+            journal_change(JournalAction::Uninstall, &self.package, self.backend.name(), None, None, self.journal_id);
         }
         Ok(())
     }
 }
 
+/// Append a change to the journal, returning its id
+///
+/// Nothing is journaled in dry-run mode. A journal that can't be written only
+/// produces a warning, since the change itself already happened.
+fn journal_change(
+    action: JournalAction,
+    package: &str,
+    backend: &str,
+    before: Option<String>,
+    after: Option<String>,
+    undoes: Option<u64>,
+) -> Option<u64> {
+    if is_dry_run() {
+        return None;
+    }
+    let appended = Journal::load()
+        .and_then(|mut journal| journal.append(action, package, backend, before, after, undoes).map(|e| e.id));
+    match appended {
+        Ok(id) => Some(id),
+        Err(e) => {
+            eprintln!("   ⚠ Could not write to the journal: {}", e);
+            None
+        }
+    }
+}
+
+/// A package its backend just installed, before verification, shims and records
+struct PendingInstall {
+    tx: Transaction,
+    /// Version installed before this change, if any
+    before: Option<String>,
+    /// Native commands that performed the install
+    commands: Vec<BackendCommand>,
+}
+
+impl PendingInstall {
+    /// Verify, shim, record and journal the install (see [`complete_install`])
+    fn complete(self, verify_hash: Option<&str>, undoes: Option<u64>) -> Result<Transaction, OneInstallError> {
+        complete_install(self.tx, verify_hash, self.before, &self.commands, undoes)
+    }
+}
+
 /// Main entry point for 1install operations
-pub async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
+pub async fn run(cli: Cli) -> Result<(), OneInstallError> {
     // Track active user ping
     crate::telemetry::TelemetryClient::track_event(crate::telemetry::TelemetryEvent::UserPing);
 
+    set_output_format(cli.output);
+    let dry_run = cli.dry_run.then(enable_dry_run);
+    // Commands that change installed packages may move the binaries shims point at
+    let changes_packages = matches!(
+        cli.command,
+        Commands::Install { .. } | Commands::Update { .. } | Commands::Upgrade { .. }
+            | Commands::Sync { .. } | Commands::Undo { .. }
+    );
+
     match cli.command {
-        Commands::Search { query, limit } => {
-            search_packages(query, limit).await?;
+        Commands::Search { query, limit, stream, refresh, offline } => {
+            search_packages(query, limit, stream, refresh, offline).await?;
         }
-        Commands::Install { package, backend, verify } => {
-            install_package(&package, backend.as_deref(), verify.as_deref())?;
+        Commands::Install { packages, backend, verify, atomic, no_fallback, smart, prefer } => {
+            let config = load_config().unwrap_or_default();
+            let fallback = !no_fallback && config.behavior.fallback;
+            // --prefer implies --smart; install.smart turns it on for every install
+            let smart = (smart || prefer.is_some() || config.install.smart)
+                .then(|| prefer.unwrap_or(config.install.policy));
+            match packages.as_slice() {
+                [package] => install_package(package, backend.as_deref(), verify.as_deref(), fallback, smart)?,
+                _ if verify.is_some() => {
+                    return Err(OneInstallError::InvalidInput(
+                        "--verify takes a single hash, so it can only be used when installing one package".to_string()
+                    ));
+                }
+                _ => install_packages(&packages, backend.as_deref(), atomic, fallback, smart)?,
+            }
+        }
+        Commands::Info { package, backend } => {
+            let package = package.without_version("info")?;
+            show_package_info(&package.name, package.backend_or(backend.as_deref())?).await?;
+        }
+        Commands::List { backend } => {
+            list_installed_packages(backend.as_deref()).await?;
+        }
+        Commands::Outdated { backend } => {
+            list_outdated_packages(backend.as_deref()).await?;
+        }
+        Commands::Upgrade { package, all, backend } => {
+            if all {
+                upgrade_all_packages(backend.as_deref())?;
+            } else if let Some(package) = package {
+                update_package(&package, backend.as_deref())?;
+            }
         }
         Commands::Backends => {
-            list_backends();
+            list_backends()?;
         }
         Commands::Config { action } => {
             handle_config(action)?;
@@ -90,7 +238,20 @@ pub async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             update_package(&package, backend.as_deref())?;
         }
         Commands::Uninstall { package, backend } => {
-            uninstall_package(&package, backend.as_deref())?;
+            let package = package.without_version("uninstall")?;
+            uninstall_package(&package.name, package.backend_or(backend.as_deref())?)?;
+        }
+        Commands::Sync { manifest, locked } => {
+            sync_manifest(manifest.as_deref(), locked)?;
+        }
+        Commands::Lock { manifest } => {
+            lock_manifest(manifest.as_deref())?;
+        }
+        Commands::History { package, limit } => {
+            show_history(package.as_deref(), limit)?;
+        }
+        Commands::Undo { id } => {
+            undo_change(id)?;
         }
         Commands::SelfInstall => {
             handle_self_install()?;
@@ -99,61 +260,176 @@ pub async fn run(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             Doctor::run()?;
         }
     }
+
+    if changes_packages {
+        auto_refresh_shims();
+    }
+    if let Some(recorder) = dry_run {
+        render_command_plan(&recorder.plan());
+    }
     Ok(())
 }
 
 /// Search for packages across all available backends
-pub async fn search_packages(query: String, limit: usize) -> Result<(), Box<dyn std::error::Error>> {
-    println!("🔍 Searching for '{}'...\n", query);
+///
+/// With `stream`, each backend's results are printed as soon as it finishes
+/// and the combined ranking follows once every backend is done. Results are
+/// cached per backend for `search.cache_ttl_secs`; `refresh` skips the cache
+/// lookup and `offline` answers from the cache alone, whatever its age.
+pub async fn search_packages(
+    query: String,
+    limit: usize,
+    stream: bool,
+    refresh: bool,
+    offline: bool,
+) -> Result<(), OneInstallError> {
+    let human = is_human_output();
+    // Incremental output only makes sense for people watching a terminal
+    let stream = stream && human;
+    if human {
+        println!("🔍 Searching for '{}'...\n", query);
+    }
     let start_time = Instant::now();
     
-    let backends = get_all_available_backends();
+    let resolver = BackendResolver::from_config();
+    let backends = resolver.available();
+    let aggregator = SearchAggregator::new(resolver.priority().to_vec()).with_aliases(load_aliases());
     
     if backends.is_empty() {
-        println!("No package managers available on this system.");
-        return Ok(());
+        return no_backends_available::<PackageResult>();
     }
     
-    println!("   Searching {} backends: {}", 
-        backends.len(),
-        backends.iter().map(|b| b.name()).collect::<Vec<_>>().join(", ")
-    );
-    println!();
+    if human {
+        println!("   Searching {} backends: {}", 
+            backends.len(),
+            backends.iter().map(|b| b.name()).collect::<Vec<_>>().join(", ")
+        );
+        println!();
+    }
     
     TelemetryClient::track_event(TelemetryEvent::SearchStarted { 
         query_length: query.len(), 
         backends_count: backends.len() 
     });
     
-    type SearchResult = (String, Result<Vec<PackageResult>, Box<dyn std::error::Error + Send + Sync>>);
-    let mut join_set: tokio::task::JoinSet<SearchResult> = tokio::task::JoinSet::new();
+    let config = load_config().unwrap_or_default();
+    let verbose = config.behavior.verbose;
+    let timeout = Duration::from_secs(config.search.timeout_secs);
+    let ttl = Duration::from_secs(config.search.cache_ttl_secs);
+    let use_cache = offline || !ttl.is_zero();
+    // Offline searches accept cached results of any age
+    let max_age = if offline { None } else { Some(ttl) };
+    
+    let mut cache = if use_cache {
+        SearchCache::load().unwrap_or_else(|e| {
+            if verbose {
+                eprintln!("   Warning: ignoring unreadable search cache: {}", e);
+            }
+            SearchCache::default()
+        })
+    } else {
+        SearchCache::default()
+    };
+    
+    let mut join_set: tokio::task::JoinSet<(BackendSearchReport, Vec<PackageResult>)> = tokio::task::JoinSet::new();
     let query_shared = std::sync::Arc::new(query.clone());
+    let mut pending: Vec<String> = Vec::new();
     
     for backend in backends {
+        let name = backend.name().to_string();
+        
+        let hit = (use_cache && !refresh).then(|| cache.get(&name, &query, max_age)).flatten();
+        if let Some(entry) = hit {
+            if verbose {
+                eprintln!("   💾 Cache hit: {} ({} results, {}s old)", name, entry.results.len(), entry.age().as_secs());
+            }
+            let results = entry.results.clone();
+            let report = BackendSearchReport {
+                backend: name.clone(),
+                outcome: SearchOutcome::Completed(results.len()),
+                elapsed: Duration::ZERO,
+                cached: true,
+            };
+            pending.push(name);
+            join_set.spawn(async move { (report, results) });
+            continue;
+        }
+        
+        if offline {
+            if verbose {
+                eprintln!("   💾 Cache miss: {} (skipped, offline)", name);
+            }
+            continue;
+        }
+        if verbose && use_cache {
+            eprintln!("   💾 Cache {}: {}", if refresh { "refresh" } else { "miss" }, name);
+        }
+        
         let q = query_shared.clone();
+        pending.push(name.clone());
         join_set.spawn(async move {
-            match backend.search(&q) {
-                Ok(results) => (backend.name().to_string(), Ok(results)),
-                Err(e) => (backend.name().to_string(), Err(e)),
-            }
+            let started = Instant::now();
+            
+            // Backend CLIs block, so run them off the async workers. The runner
+            // kills any child still running at the deadline; the outer timeout
+            // (with a little slack) catches backends stuck somewhere else.
+            let task = tokio::task::spawn_blocking(move || with_timeout(timeout, || backend.search(&q)));
+            let (outcome, results) = match tokio::time::timeout(timeout + SEARCH_TIMEOUT_SLACK, task).await {
+                Ok(Ok(Ok(results))) => (SearchOutcome::Completed(results.len()), results),
+                Ok(Ok(Err(OneInstallError::TimedOut { .. }))) | Err(_) => (SearchOutcome::TimedOut, Vec::new()),
+                Ok(Ok(Err(e))) => (SearchOutcome::Failed(e.to_string()), Vec::new()),
+                Ok(Err(e)) => (SearchOutcome::Failed(format!("search task panicked: {}", e)), Vec::new()),
+            };
+            
+            let report = BackendSearchReport { backend: name, outcome, elapsed: started.elapsed(), cached: false };
+            (report, results)
         });
     }
     
     let mut all_results: Vec<PackageResult> = Vec::new();
+    let mut reports: Vec<BackendSearchReport> = Vec::new();
+    
+    if stream {
+        render_search_progress(&pending);
+    }
     
     while let Some(res) = join_set.join_next().await {
         match res {
-            Ok((_name, Ok(mut results))) => {
+            Ok((report, mut results)) => {
+                if stream {
+                    pending.retain(|name| *name != report.backend);
+                    aggregator.rank_results(&query, &mut results);
+                    clear_search_progress();
+                    render_streamed_results(&report, &results, STREAMED_RESULTS_PER_BACKEND);
+                    render_search_progress(&pending);
+                }
+                if !report.cached && !ttl.is_zero() && matches!(report.outcome, SearchOutcome::Completed(_)) {
+                    cache.insert(&report.backend, &query, results.clone());
+                }
                 all_results.append(&mut results);
-            }
-            Ok((name, Err(e))) => {
-                eprintln!("   Warning: {} search failed: {}", name, e);
+                reports.push(report);
             }
             Err(e) => {
                 eprintln!("   Error: Search task panicked: {}", e);
             }
         }
     }
+    reports.sort_by(|a, b| a.backend.cmp(&b.backend));
+    
+    if !offline && !ttl.is_zero() && reports.iter().any(|r| !r.cached) {
+        // Stale entries stay around for --offline until the retention period ends
+        cache.prune(ttl.max(Duration::from_secs(config.search.cache_retention_secs)));
+        if let Err(e) = cache.save() {
+            if verbose {
+                eprintln!("   Warning: could not save search cache: {}", e);
+            }
+        }
+    }
+    
+    if stream {
+        clear_search_progress();
+        println!("\n📊 Final ranking\n");
+    }
     
     let duration = start_time.elapsed();
     TelemetryClient::track_event(TelemetryEvent::SearchFinished { 
@@ -161,216 +437,1439 @@ pub async fn search_packages(query: String, limit: usize) -> Result<(), Box<dyn
         duration_ms: duration.as_millis() 
     });
     
-    SearchAggregator::rank_results(&query, &mut all_results);
+    aggregator.rank_results(&query, &mut all_results);
+    if !human {
+        all_results.truncate(limit);
+        return emit_list(&all_results);
+    }
     render_search_results(&all_results, limit);
     
+    // Streaming already reported each backend's status as it finished
+    if !stream && (verbose || reports.iter().any(BackendSearchReport::is_problem)) {
+        println!();
+        render_search_summary(&reports);
+    }
+    
     Ok(())
 }
 
-/// Install a package using the appropriate backend
-fn install_package(package: &str, backend_name: Option<&str>, verify_hash: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
-    println!("🔍 Detecting system...");
-    let start_time = Instant::now();
-    let backend = get_backend(backend_name)?;
-    let backend_name_str = backend.name().to_string();
+/// Show package metadata from every backend that knows the package
+pub async fn show_package_info(package: &str, backend_filter: Option<&str>) -> Result<(), OneInstallError> {
+    let backends = get_matching_backends(backend_filter)?;
+    if backends.is_empty() {
+        return no_backends_available::<PackageInfo>();
+    }
     
-    println!("   Backend: {}", backend_name_str);
-    println!("   Status: ✓ Available\n");
+    if is_human_output() {
+        println!("🔍 Looking up '{}'...\n", package);
+    }
     
-    TelemetryClient::track_event(TelemetryEvent::InstallStarted { 
-        backend: backend_name_str.clone() 
-    });
+    type InfoResult = (usize, String, Result<Option<PackageInfo>, OneInstallError>);
+    let mut join_set: tokio::task::JoinSet<InfoResult> = tokio::task::JoinSet::new();
+    let package_shared = std::sync::Arc::new(package.to_string());
     
-    let mut tx = Transaction::new(package, backend);
+    for (index, backend) in backends.into_iter().enumerate() {
+        let p = package_shared.clone();
+        // Backend calls run subprocesses, so keep them off the async workers
+        join_set.spawn_blocking(move || {
+            (index, backend.name().to_string(), backend.info(&p))
+        });
+    }
     
-    println!("📦 Installing {}...", package);
-    let install_result = tx.backend.install(package);
+    let mut infos: Vec<(usize, PackageInfo)> = Vec::new();
     
-    match install_result {
-        Ok(_) => tx.installed = true,
-        Err(e) => {
-            let duration = start_time.elapsed();
-            TelemetryClient::track_event(TelemetryEvent::InstallFinished { 
-                backend: backend_name_str, 
-                success: false, 
-                duration_ms: duration.as_millis() 
-            });
-            let _ = tx.rollback();
-            return Err(e);
+    while let Some(res) = join_set.join_next().await {
+        match res {
+            Ok((index, _name, Ok(Some(info)))) => infos.push((index, info)),
+            Ok((_, _, Ok(None))) => {}
+            Ok((_, name, Err(e))) => {
+                eprintln!("   Warning: {} lookup failed: {}", name, e);
+            }
+            Err(e) => {
+                eprintln!("   Error: Lookup task panicked: {}", e);
+            }
         }
     }
     
-    // Find binary for verification and shims
-    let binary_path = find_binary(package);
+    // Keep backend order stable regardless of which lookup finished first
+    infos.sort_by_key(|(index, _)| *index);
+    let infos: Vec<PackageInfo> = infos.into_iter().map(|(_, info)| info).collect();
+    if infos.is_empty() {
+        return Err(OneInstallError::PackageNotFound {
+            package: package.to_string(),
+            backend: backend_filter.map(str::to_string),
+        });
+    }
+    if !is_human_output() {
+        return emit_list(&infos);
+    }
+    render_package_info(package, &infos);
     
-    // Integrity Verification
-    if let Some(hash) = verify_hash {
-        if let Some(ref path) = binary_path {
-            println!("🛡️ Verifying integrity...");
-            if let Err(e) = verify_file_hash(path, hash) {
-                println!("   ❌ Integrity verification failed: {}", e);
-                let _ = tx.rollback();
-                return Err("Security check failed: Package hash mismatch!".into());
+    Ok(())
+}
+
+/// List installed packages across all available backends
+pub async fn list_installed_packages(backend_filter: Option<&str>) -> Result<(), OneInstallError> {
+    let backends = get_matching_backends(backend_filter)?;
+    if backends.is_empty() {
+        return no_backends_available::<PackageResult>();
+    }
+    
+    if is_human_output() {
+        println!("📋 Listing installed packages from: {}\n",
+            backends.iter().map(|b| b.name()).collect::<Vec<_>>().join(", ")
+        );
+    }
+    
+    type ListResult = (String, Result<Vec<PackageResult>, OneInstallError>);
+    let mut join_set: tokio::task::JoinSet<ListResult> = tokio::task::JoinSet::new();
+    
+    for backend in backends {
+        join_set.spawn_blocking(move || {
+            (backend.name().to_string(), backend.list_installed())
+        });
+    }
+    
+    let mut all_packages: Vec<PackageResult> = Vec::new();
+    
+    while let Some(res) = join_set.join_next().await {
+        match res {
+            Ok((_name, Ok(mut packages))) => {
+                all_packages.append(&mut packages);
+            }
+            Ok((name, Err(e))) => {
+                eprintln!("   Warning: {} listing failed: {}", name, e);
+            }
+            Err(e) => {
+                eprintln!("   Error: List task panicked: {}", e);
             }
-            println!("   ✓ Hash verified.");
-        } else {
-            println!("   ⚠️ Could not locate binary for integrity verification.");
-            let _ = tx.rollback();
-            return Err("Security check failed: Could not locate binary to verify!".into());
         }
     }
     
-    // Handle shim creation
-    let config = load_config().unwrap_or_default();
-    if config.behavior.create_shims {
-        if let Some(ref path) = binary_path {
-            match create_shim_internal(package, path, tx.backend.name()) {
-                Ok(shim_path) => {
-                    println!("   ✓ Created shim: {}", shim_path.display());
-                    tx.shim_created = true;
-                }
-                Err(e) => eprintln!("   ⚠ Shim creation failed: {}", e),
+    all_packages.sort_by(|a, b| a.source.cmp(&b.source).then_with(|| a.name.cmp(&b.name)));
+    if !is_human_output() {
+        return emit_list(&all_packages);
+    }
+    render_installed_packages(&all_packages, &StateDb::load().unwrap_or_default());
+    
+    Ok(())
+}
+
+/// List outdated packages across all available backends
+pub async fn list_outdated_packages(backend_filter: Option<&str>) -> Result<(), OneInstallError> {
+    let backends = get_matching_backends(backend_filter)?;
+    if backends.is_empty() {
+        return no_backends_available::<OutdatedPackage>();
+    }
+    
+    if is_human_output() {
+        println!("🔍 Checking for updates in: {}\n",
+            backends.iter().map(|b| b.name()).collect::<Vec<_>>().join(", ")
+        );
+    }
+    
+    type OutdatedResult = (String, Result<Vec<OutdatedPackage>, OneInstallError>);
+    let mut join_set: tokio::task::JoinSet<OutdatedResult> = tokio::task::JoinSet::new();
+    
+    for backend in backends {
+        join_set.spawn_blocking(move || {
+            (backend.name().to_string(), backend.outdated())
+        });
+    }
+    
+    let mut all_outdated: Vec<OutdatedPackage> = Vec::new();
+    
+    while let Some(res) = join_set.join_next().await {
+        match res {
+            Ok((_name, Ok(mut packages))) => {
+                all_outdated.append(&mut packages);
+            }
+            Ok((name, Err(e))) => {
+                eprintln!("   Warning: {} update check failed: {}", name, e);
+            }
+            Err(e) => {
+                eprintln!("   Error: Update check task panicked: {}", e);
             }
-        } else if config.behavior.verbose {
-            println!("   ℹ Could not locate binary for shim creation");
         }
     }
     
-    println!("\n✓ {} installed successfully!", package);
-    
-    let duration = start_time.elapsed();
-    TelemetryClient::track_event(TelemetryEvent::InstallFinished { 
-        backend: backend_name_str, 
-        success: true, 
-        duration_ms: duration.as_millis() 
-    });
+    all_outdated.sort_by(|a, b| a.source.cmp(&b.source).then_with(|| a.name.cmp(&b.name)));
+    if !is_human_output() {
+        return emit_list(&all_outdated);
+    }
+    render_outdated_packages(&all_outdated);
     
     Ok(())
 }
 
-/// Update a package
-fn update_package(package: &str, backend_name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
-    println!("🔍 Detecting system...");
-    let backend = get_backend(backend_name)?;
+/// Upgrade everything on every available backend, continuing past failures
+fn upgrade_all_packages(backend_filter: Option<&str>) -> Result<(), OneInstallError> {
+    let backends = get_matching_backends(backend_filter)?;
+    if backends.is_empty() {
+        println!("No package managers available on this system.");
+        return Ok(());
+    }
     
-    println!("   Backend: {}", backend.name());
-    println!("   Status: ✓ Available\n");
+    let mut outcomes: Vec<(String, Result<(), OneInstallError>)> = Vec::new();
     
-    println!("🔄 Updating {}...", package);
-    backend.update(package)?;
+    for backend in &backends {
+        println!("🔄 Upgrading all {} packages...", backend.name());
+        let outdated = if is_dry_run() { Vec::new() } else { backend.outdated().unwrap_or_default() };
+        let outcome = backend.upgrade_all();
+        match outcome {
+            Ok(()) => journal_upgrades(backend.as_ref(), outdated),
+            Err(OneInstallError::Unsupported(ref reason)) => println!("   ⚠ {}", reason),
+            Err(ref e) => eprintln!("   ✗ {}", e),
+        }
+        outcomes.push((backend.name().to_string(), outcome));
+        println!();
+    }
     
-    println!("\n✓ {} updated successfully!", package);
+    render_upgrade_summary(&outcomes);
+    
+    // Backends that can't check for updates are skipped, not failed
+    let failed = outcomes.iter()
+        .filter(|(_, o)| o.as_ref().is_err_and(|e| !matches!(e, OneInstallError::Unsupported(_))))
+        .count();
+    if failed > 0 {
+        return Err(OneInstallError::PartialFailure(
+            format!("{} of {} backends failed to upgrade", failed, outcomes.len())
+        ));
+    }
     Ok(())
 }
 
-/// Uninstall a package
-fn uninstall_package(package: &str, backend_name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+/// Journal each package a whole-backend upgrade touched
+fn journal_upgrades(backend: &dyn Backend, outdated: Vec<OutdatedPackage>) {
+    if outdated.is_empty() {
+        return;
+    }
+    let installed = backend.list_installed().unwrap_or_default();
+    for package in outdated {
+        let after = installed.iter()
+            .find(|p| p.name == package.name)
+            .and_then(|p| p.version.clone())
+            .or(package.latest);
+        journal_change(JournalAction::Update, &package.name, backend.name(), package.current, after, None);
+    }
+}
+
+/// Install a package using the appropriate backend
+///
+/// Without a backend named, a choice remembered in `install.choices` or,
+/// with `smart`, a search of every backend (see [`pick_backend`]) decides
+/// instead of the priority list.
+/// With `fallback`, a package the chosen backend can't install is tried on
+/// the other enabled backends (see [`install_with_fallback`]). A backend
+/// named explicitly is never substituted.
+fn install_package(
+    spec: &PackageSpec,
+    backend_name: Option<&str>,
+    verify_hash: Option<&str>,
+    fallback: bool,
+    smart: Option<InstallPolicy>,
+) -> Result<(), OneInstallError> {
     println!("🔍 Detecting system...");
-    let backend = get_backend(backend_name)?;
+    let start_time = Instant::now();
+    let explicit = spec.backend_or(backend_name)?;
+    let chosen = match explicit {
+        None => pick_backend(&spec.name, smart)?,
+        Some(_) => None,
+    };
+    let backend = get_backend(explicit.or(chosen.as_deref()))?;
+    let backend_name_str = backend.name().to_string();
     
-    println!("   Backend: {}", backend.name());
+    println!("   Backend: {}", backend_name_str);
     println!("   Status: ✓ Available\n");
     
-    println!("🗑️ Uninstalling {}...", package);
-    backend.uninstall(package)?;
+    TelemetryClient::track_event(TelemetryEvent::InstallStarted { 
+        backend: backend_name_str.clone() 
+    });
     
-    // Remove shim if it exists
-    let mut registry = ShimRegistry::load()?;
-    if registry.remove(package).is_some() {
-        println!("   ✓ Removed associated shim");
-        registry.save()?;
-        
-        let shim_dir = get_shim_dir();
-        #[cfg(windows)]
-        {
-            let _ = std::fs::remove_file(shim_dir.join(format!("{}.cmd", package)));
-            let _ = std::fs::remove_file(shim_dir.join(format!("{}.ps1", package)));
+    let result = match install_in_transaction(&spec.name, backend.into(), verify_hash, spec.version.as_ref(), None) {
+        Err(e) if fallback && explicit.is_none() && can_fall_back(&e) => {
+            install_with_fallback(spec, &backend_name_str).ok_or(e)
         }
-        #[cfg(not(windows))]
-        {
-            let _ = std::fs::remove_file(shim_dir.join(package));
+        other => other,
+    };
+    
+    let duration = start_time.elapsed();
+    TelemetryClient::track_event(TelemetryEvent::InstallFinished { 
+        backend: backend_name_str.clone(), 
+        success: result.is_ok(), 
+        duration_ms: duration.as_millis() 
+    });
+    
+    let tx = result?;
+    if is_dry_run() {
+        return Ok(());
+    }
+    match tx.backend.name() {
+        via if via != backend_name_str => {
+            println!("\n✓ {} installed successfully via {} (fallback from {})!", spec, via, backend_name_str);
         }
+        _ => println!("\n✓ {} installed successfully!", spec),
     }
-    
-    println!("\n✓ {} uninstalled successfully!", package);
     Ok(())
 }
 
-/// Helper to get a backend
-fn get_backend(backend_name: Option<&str>) -> Result<Box<dyn Backend>, Box<dyn std::error::Error>> {
-    if let Some(name) = backend_name {
-        let all = get_all_available_backends();
-        all.into_iter()
-            .find(|b| b.name() == name)
-            .ok_or_else(|| format!("Backend '{}' not available", name).into())
-    } else {
-        let context = OsContext::detect();
-        get_backend_for_context(&context)
+/// Backend for a package installed without naming one
+///
+/// A choice remembered in `install.choices` wins; otherwise, with `smart`,
+/// every backend is searched (see [`choose_install_backend`]). `None` means
+/// "use the default backend".
+fn pick_backend(package: &str, smart: Option<InstallPolicy>) -> Result<Option<String>, OneInstallError> {
+    let config = load_config().unwrap_or_default();
+    if let Some(backend) = config.install.choices.get(package) {
+        println!("   Using {} for {} (install.choices)", backend, package);
+        return Ok(Some(backend.clone()));
+    }
+    match smart {
+        Some(policy) => choose_install_backend(package, policy, can_prompt(config.behavior.auto_confirm)),
+        None => Ok(None),
     }
 }
 
-/// Find a binary in PATH
-fn find_binary(name: &str) -> Option<PathBuf> {
-    if cfg!(windows) {
-        let output = std::process::Command::new("where")
-            .arg(name)
-            .output().ok()?;
-        if output.status.success() {
-            let s = String::from_utf8_lossy(&output.stdout);
-            s.lines().next().map(PathBuf::from)
-        } else {
-            None
-        }
+/// Search every enabled backend for `package` and pick one according to `policy`
+///
+/// Prints the candidates and the reason for the pick. With `interactive` and
+/// more than one candidate, the user makes the final call and may have it
+/// remembered. Returns `None` when no backend lists the exact name.
+fn choose_install_backend(
+    package: &str,
+    policy: InstallPolicy,
+    interactive: bool,
+) -> Result<Option<String>, OneInstallError> {
+    println!("🧭 Looking for '{}' in every backend (policy: {})...", package, policy);
+    let resolver = BackendResolver::from_config();
+    let aggregator = SearchAggregator::new(resolver.priority().to_vec()).with_aliases(load_aliases());
+    let results = search_backends(resolver.available(), package);
+    
+    let Some(choice) = choose_backend(package, results, policy, &aggregator) else {
+        println!("   No backend has a package named exactly '{}'; using the default\n", package);
+        return Ok(None);
+    };
+    render_backend_choice(&choice);
+    println!("   → {}: {}", choice.best().source, choice.reason);
+    
+    if !interactive || choice.candidates.len() < 2 {
+        println!();
+        return Ok(Some(choice.best().source.clone()));
+    }
+    let index = prompt_choice(&format!("Install {} from", package), choice.candidates.len(), 0)
+        .ok_or(OneInstallError::Cancelled)?;
+    let picked = choice.candidates[index].source.clone();
+    // Remembering the policy's own pick would change nothing
+    if index > 0 && !is_dry_run() && confirm(&format!("Always use {} for {}?", picked, package), false) {
+        remember_choice(package, &picked);
+    }
+    println!();
+    Ok(Some(picked))
+}
+
+/// Save a per-package backend choice to `install.choices`
+fn remember_choice(package: &str, backend: &str) {
+    let mut config = load_config().unwrap_or_default();
+    config.install.choices.insert(package.to_string(), backend.to_string());
+    match save_config(&config) {
+        Ok(()) => println!("   ✓ Saved as install.choices.{} (set it to \"\" to forget)", package),
+        Err(e) => eprintln!("   Warning: could not save the choice: {}", e),
+    }
+}
+
+/// Search several backends in parallel, going through the search cache
+///
+/// Each result's source is set to the backend that returned it, so it can
+/// be handed straight back to [`get_backend`] (pipx reports itself as pip).
+fn search_backends(backends: Vec<Box<dyn Backend>>, query: &str) -> Vec<PackageResult> {
+    let config = load_config().unwrap_or_default();
+    let timeout = Duration::from_secs(config.search.timeout_secs);
+    let ttl = Duration::from_secs(config.search.cache_ttl_secs);
+    let mut cache = if ttl.is_zero() {
+        SearchCache::default()
     } else {
-        let output = std::process::Command::new("which")
-            .arg(name)
-            .output().ok()?;
-        if output.status.success() {
-            let s = String::from_utf8_lossy(&output.stdout);
-            Some(PathBuf::from(s.trim()))
-        } else {
-            None
+        SearchCache::load().unwrap_or_default()
+    };
+    
+    let mut found: Vec<(String, Vec<PackageResult>)> = Vec::new();
+    let mut misses = Vec::new();
+    for backend in backends {
+        match cache.get(backend.name(), query, Some(ttl)) {
+            Some(entry) if !ttl.is_zero() => found.push((backend.name().to_string(), entry.results.clone())),
+            _ => misses.push(backend),
+        }
+    }
+    
+    let searched: Vec<(String, Vec<PackageResult>)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = misses.iter()
+            .map(|backend| scope.spawn(move || (backend.name().to_string(), with_timeout(timeout, || backend.search(query)))))
+            .collect();
+        handles.into_iter()
+            .filter_map(|handle| match handle.join() {
+                Ok((name, Ok(results))) => Some((name, results)),
+                _ => None,
+            })
+            .collect()
+    });
+    if !ttl.is_zero() && !searched.is_empty() {
+        for (name, results) in &searched {
+            cache.insert(name, query, results.clone());
         }
+        cache.prune(ttl.max(Duration::from_secs(config.search.cache_retention_secs)));
+        let _ = cache.save();
     }
+    
+    found.into_iter()
+        .chain(searched)
+        .flat_map(|(name, results)| results.into_iter().map(move |mut r| {
+            r.source = name.clone();
+            r
+        }))
+        .collect()
 }
 
-/// Internal shim creation logic
-fn create_shim_internal(name: &str, target: &Path, backend_name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let shim_path = shims::create_shim(name, target)?;
-    let mut registry = ShimRegistry::load()?;
-    registry.add(name.to_string(), target.to_path_buf(), backend_name.to_string());
-    registry.save()?;
-    Ok(shim_path)
+/// Whether an install error means "try another backend" rather than "stop"
+fn can_fall_back(error: &OneInstallError) -> bool {
+    matches!(
+        error,
+        OneInstallError::CommandFailed { .. } | OneInstallError::PackageNotFound { .. } | OneInstallError::Unsupported(_)
+    )
 }
 
-/// List available backends
-fn list_backends() {
-    let backends = get_all_available_backends();
-    let names: Vec<&str> = backends.iter().map(|b| b.name()).collect();
-    render_backends(&names);
+/// Try the other enabled backends, in priority order, for a package `tried` couldn't install
+///
+/// A backend is only attempted if searching it turns up a package with
+/// exactly this name. Returns the first successful install, if any.
+fn install_with_fallback(spec: &PackageSpec, tried: &str) -> Option<Transaction> {
+    println!("   ↪ {} could not install {}; trying other backends...", tried, spec.name);
+    let timeout = Duration::from_secs(load_config().unwrap_or_default().search.timeout_secs);
+    let aliases = load_aliases();
+    
+    for backend in BackendResolver::from_config().available() {
+        let name = backend.name().to_string();
+        if name == tried {
+            continue;
+        }
+        let native = aliases.package_for(&spec.name, &name);
+        let found = with_timeout(timeout, || backend.search(&native)).ok()
+            .and_then(|results| results.into_iter().find(|r| r.name.eq_ignore_ascii_case(&native) && r.has_details()));
+        let Some(found) = found else {
+            println!("   · {}: no package named {}", name, native);
+            continue;
+        };
+        
+        println!("   🔎 {}: found {}{}", name, found.name,
+            found.version.map(|v| format!(" {}", v)).unwrap_or_default());
+        match install_in_transaction(&spec.name, backend.into(), None, spec.version.as_ref(), None) {
+            Ok(tx) => return Some(tx),
+            Err(e) => eprintln!("   ✗ {} failed: {}", name, e.to_string().lines().next().unwrap_or_default()),
+        }
+    }
+    None
 }
 
-/// Handle config subcommands
-fn handle_config(action: ConfigAction) -> Result<(), Box<dyn std::error::Error>> {
-    match action {
-        ConfigAction::Get { key } => {
-            let config = load_config()?;
-            match config.get(&key) {
-                Some(value) => println!("{} = {}", key, value),
-                None => eprintln!("Unknown config key: {}", key),
+/// Install several packages, one native invocation per backend
+///
+/// Packages without a backend go through [`pick_backend`] first.
+/// Backends run in parallel, except that those needing sudo share one lane
+/// so their prompts can't interleave; verification, shims, state and the
+/// journal are then handled serially once every backend is done. When a
+/// backend's batch fails, its packages are retried one at a time so a single
+/// bad name doesn't sink the rest, and with `fallback` whatever still failed
+/// goes through the fallback chain. With `atomic`, any failure rolls back
+/// every package this command installed.
+fn install_packages(
+    packages: &[PackageSpec],
+    backend_name: Option<&str>,
+    atomic: bool,
+    fallback: bool,
+    smart: Option<InstallPolicy>,
+) -> Result<(), OneInstallError> {
+    println!("🔍 Detecting system...");
+    let mut chosen: HashMap<&str, String> = HashMap::new();
+    for spec in packages {
+        if spec.backend_or(backend_name)?.is_none() && !chosen.contains_key(spec.name.as_str()) {
+            if let Some(backend) = pick_backend(&spec.name, smart)? {
+                chosen.insert(&spec.name, backend);
             }
         }
-        ConfigAction::Set { key, value } => {
-            let mut config = load_config()?;
-            config.set(&key, &value)?;
-            save_config(&config)?;
-            println!("✓ Set {} = {}", key, value);
+    }
+    let needs_default = packages.iter().any(|s| s.backend.is_none() && !chosen.contains_key(s.name.as_str()));
+    let default_backend = if backend_name.is_none() && needs_default {
+        get_backend(None)?.name().to_string()
+    } else {
+        String::new()
+    };
+    
+    let mut groups: BTreeMap<&str, Vec<&PackageSpec>> = BTreeMap::new();
+    for spec in packages {
+        let backend = spec.backend_or(backend_name)?
+            .or_else(|| chosen.get(spec.name.as_str()).map(String::as_str))
+            .unwrap_or(&default_backend);
+        let group = groups.entry(backend).or_default();
+        if !group.iter().any(|s| s.name == spec.name) {
+            group.push(spec);
+        }
+    }
+    for (name, group) in &groups {
+        let specs: Vec<String> = group.iter().map(|s| s.to_string()).collect();
+        println!("   {}: {}", name, specs.join(", "));
+    }
+    println!();
+    
+    // One lane per backend, except that every sudo backend shares a lane
+    let mut root_lane = Vec::new();
+    let mut lanes = Vec::new();
+    for (name, group) in &groups {
+        let backend = get_backend(Some(name)).map(Arc::<dyn Backend>::from).map_err(|e| e.to_string());
+        match backend {
+            Ok(ref b) if b.needs_root() => root_lane.push((*name, backend, group)),
+            _ => lanes.push(vec![(*name, backend, group)]),
+        }
+    }
+    if !root_lane.is_empty() {
+        prime_sudo();
+        lanes.push(root_lane);
+    }
+    
+    // Telemetry needs the async runtime, which the lane threads don't have,
+    // so events are sent from here before and after the lanes run
+    for name in groups.keys() {
+        TelemetryClient::track_event(TelemetryEvent::InstallStarted { backend: name.to_string() });
+    }
+    let batches: Vec<BatchResult> = std::thread::scope(|scope| {
+        let handles: Vec<_> = lanes.iter()
+            .map(|lane| scope.spawn(move || {
+                lane.iter()
+                    .map(|(name, backend, group)| {
+                        let start_time = Instant::now();
+                        let results = install_batch(name, backend.clone(), group);
+                        (name.to_string(), start_time.elapsed(), results)
+                    })
+                    .collect::<Vec<_>>()
+            }))
+            .collect();
+        handles.into_iter().zip(&lanes).flat_map(|(handle, lane)| {
+            handle.join().unwrap_or_else(|_| {
+                lane.iter().map(|(name, _, group)| {
+                    let results = group.iter().map(|spec| {
+                        let status = InstallStatus::Failed("install thread panicked".to_string());
+                        (InstallOutcome { package: spec.name.clone(), backend: name.to_string(), status }, None)
+                    }).collect();
+                    (name.to_string(), Duration::ZERO, results)
+                }).collect()
+            })
+        }).collect()
+    });
+    let installed: Vec<(InstallOutcome, Option<PendingInstall>)> = batches.into_iter()
+        .flat_map(|(backend, duration, results)| {
+            TelemetryClient::track_event(TelemetryEvent::InstallFinished {
+                backend,
+                success: results.iter().all(|(o, _)| o.status == InstallStatus::Installed),
+                duration_ms: duration.as_millis(),
+            });
+            results
+        })
+        .collect();
+    
+    // Shims, state and the journal are shared files, so finish installs one at a time
+    let mut results: Vec<(InstallOutcome, Option<Transaction>)> = installed.into_iter()
+        .map(|(mut outcome, pending)| match pending.map(|p| p.complete(None, None)) {
+            Some(Ok(tx)) => (outcome, Some(tx)),
+            Some(Err(e)) => {
+                outcome.status = InstallStatus::RolledBack(e.to_string());
+                (outcome, None)
+            }
+            None => (outcome, None),
+        })
+        .collect();
+    
+    // Failed packages that didn't name a backend get the fallback chain, one at a time
+    for (outcome, slot) in results.iter_mut().filter(|(o, _)| fallback && matches!(o.status, InstallStatus::Failed(_))) {
+        let spec = packages.iter()
+            .find(|s| s.name == outcome.package && matches!(s.backend_or(backend_name), Ok(None)));
+        if let Some(tx) = spec.and_then(|spec| install_with_fallback(spec, &outcome.backend)) {
+            outcome.backend = tx.backend.name().to_string();
+            outcome.status = InstallStatus::Installed;
+            *slot = Some(tx);
+        }
+    }
+    
+    let failed = results.iter().filter(|(o, _)| matches!(o.status, InstallStatus::Failed(_))).count();
+    let mut outcomes = Vec::with_capacity(results.len());
+    for (mut outcome, tx) in results {
+        if let (true, Some(tx)) = (atomic && failed > 0, tx) {
+            let _ = tx.rollback();
+            outcome.status = InstallStatus::RolledBack("another package failed (--atomic)".to_string());
+        }
+        if is_dry_run() && outcome.status == InstallStatus::Installed {
+            outcome.status = InstallStatus::Planned;
+        }
+        outcomes.push(outcome);
+    }
+    
+    println!();
+    render_install_summary(&outcomes);
+    
+    if failed > 0 {
+        return Err(OneInstallError::PartialFailure(format!(
+            "{} of {} packages failed to install", failed, outcomes.len()
+        )));
+    }
+    Ok(())
+}
+
+/// One backend's batch: its name, how long it took and its per-package results
+type BatchResult = (String, Duration, Vec<(InstallOutcome, Option<PendingInstall>)>);
+
+/// Run one backend's share of a multi-package install
+///
+/// Packages without a version go in one batch; versioned ones are installed
+/// individually since each needs its own native pin. Only the native
+/// installs happen here; each installed package comes back pending so the
+/// caller can finish it serially.
+fn install_batch(
+    backend_name: &str,
+    backend: Result<Arc<dyn Backend>, String>,
+    specs: &[&PackageSpec],
+) -> Vec<(InstallOutcome, Option<PendingInstall>)> {
+    let outcome = |package: &str, status| InstallOutcome {
+        package: package.to_string(),
+        backend: backend_name.to_string(),
+        status,
+    };
+    let backend = match backend {
+        Ok(backend) => backend,
+        Err(e) => {
+            return specs.iter().map(|s| (outcome(&s.name, InstallStatus::Failed(e.clone())), None)).collect();
+        }
+    };
+    // Outcomes keep the name the user typed; the backend gets its own
+    let aliases = load_aliases();
+    let unversioned: Vec<&PackageSpec> = specs.iter().copied().filter(|s| s.version.is_none()).collect();
+    let natives: Vec<String> = unversioned.iter().map(|s| aliases.package_for(&s.name, backend_name)).collect();
+    let packages: Vec<&str> = natives.iter().map(String::as_str).collect();
+    let install_one = |spec: &PackageSpec| {
+        match install_native(&spec.name, backend.clone(), spec.version.as_ref()) {
+            Ok(pending) => (outcome(&spec.name, InstallStatus::Installed), Some(pending)),
+            Err(e) => (outcome(&spec.name, InstallStatus::Failed(e.to_string())), None),
+        }
+    };
+    
+    // Versions already installed; None when the backend can't say, in which
+    // case every package counts as preexisting so rollback leaves it alone
+    let before: Option<HashMap<String, Option<String>>> = if is_dry_run() {
+        Some(HashMap::new())
+    } else {
+        backend.list_installed().ok().map(|list| list.into_iter().map(|p| (p.name, p.version)).collect())
+    };
+    
+    let mut results: Vec<_> = specs.iter().filter(|s| s.version.is_some()).map(|&s| install_one(s)).collect();
+    if packages.is_empty() {
+        return results;
+    }
+    
+    println!("📦 Installing {} with {}...", packages.join(", "), backend_name);
+    let (batch, commands) = capture_commands(|| backend.install_many(&packages));
+    
+    let batched: Vec<_> = match batch {
+        Ok(()) if is_dry_run() => {
+            println!("   (verification and shim creation skipped in dry run)");
+            unversioned.iter().map(|spec| (outcome(&spec.name, InstallStatus::Installed), None)).collect()
+        }
+        Ok(()) => unversioned.iter().zip(&packages).map(|(spec, &package)| {
+            let mut tx = Transaction::new(package, backend.clone());
+            tx.requested = spec.name.clone();
+            tx.installed = true;
+            tx.preexisting = before.as_ref().is_none_or(|b| b.contains_key(package));
+            let pending = PendingInstall {
+                tx,
+                before: before.as_ref().and_then(|b| b.get(package).cloned().flatten()),
+                commands: commands.clone(),
+            };
+            (outcome(&spec.name, InstallStatus::Installed), Some(pending))
+        }).collect(),
+        Err(e) => {
+            eprintln!("   ⚠ {} batch failed: {}", backend_name, e.to_string().lines().next().unwrap_or_default());
+            println!("   Retrying one package at a time...");
+            unversioned.iter().map(|&s| install_one(s)).collect()
+        }
+    };
+    results.extend(batched);
+    results
+}
+
+/// Install, verify and shim a package, rolling back on any failure
+///
+/// The returned transaction is already committed; callers installing several
+/// packages keep it so they can undo this install if a later one fails.
+/// `undoes` links the journal entry to the change this install reverses.
+/// `package` is translated to the backend's own name (see [`AliasDb`]).
+fn install_in_transaction(
+    package: &str,
+    backend: Arc<dyn Backend>,
+    verify_hash: Option<&str>,
+    version: Option<&VersionReq>,
+    undoes: Option<u64>,
+) -> Result<Transaction, OneInstallError> {
+    install_native(package, backend, version)?.complete(verify_hash, undoes)
+}
+
+/// Run the backend's install for a package, rolling back if it fails
+///
+/// `package` is translated to the backend's own name (see [`AliasDb`]).
+fn install_native(
+    package: &str,
+    backend: Arc<dyn Backend>,
+    version: Option<&VersionReq>,
+) -> Result<PendingInstall, OneInstallError> {
+    let native = load_aliases().package_for(package, backend.name());
+    if native != package {
+        println!("   ↪ {} is packaged as {} on {}", package, native, backend.name());
+    }
+    let mut tx = Transaction::new(native.as_str(), backend);
+    tx.requested = package.to_string();
+    let package = native.as_str();
+    let (preexisting, before) = if is_dry_run() { (false, None) } else { existing_install(tx.backend.as_ref(), package) };
+    tx.preexisting = preexisting;
+    
+    let (result, commands) = capture_commands(|| match version {
+        Some(version) => {
+            println!("📦 Installing {} {}...", package, version);
+            tx.backend.install_matching(package, version)
+        }
+        None => {
+            println!("📦 Installing {}...", package);
+            tx.backend.install(package)
+        }
+    });
+    if let Err(e) = result {
+        let _ = tx.rollback();
+        return Err(e);
+    }
+    tx.installed = true;
+    
+    Ok(PendingInstall { tx, before, commands })
+}
+
+/// Verify, shim, record and journal a package its backend just installed
+///
+/// Rolls the transaction back if verification fails.
+fn complete_install(
+    mut tx: Transaction,
+    verify_hash: Option<&str>,
+    before: Option<String>,
+    commands: &[BackendCommand],
+    undoes: Option<u64>,
+) -> Result<Transaction, OneInstallError> {
+    let package = tx.package.clone();
+    let package = package.as_str();
+    if is_dry_run() {
+        println!("   (verification and shim creation skipped in dry run)");
+        return Ok(tx);
+    }
+    
+    // Find the package's executables for verification and shims; the main
+    // one's shim gets the canonical command name even when the backend's
+    // executable differs (fdfind → fd), the rest keep their own names
+    let aliases = load_aliases();
+    let command = aliases.command_for(package);
+    let (binaries, primary) = package_binaries(tx.backend.as_ref(), package, &aliases);
+    
+    // Integrity Verification
+    if let Some(hash) = verify_hash {
+        if let Some(ref path) = primary {
+            println!("🛡️ Verifying integrity...");
+            if let Err(e) = verify_file_hash(path, hash) {
+                println!("   ❌ Integrity verification failed: {}", e);
+                let _ = tx.rollback();
+                return Err(e.into());
+            }
+            println!("   ✓ Hash verified.");
+        } else {
+            println!("   ⚠️ Could not locate binary for integrity verification.");
+            let _ = tx.rollback();
+            return Err(VerificationError::BinaryNotFound(package.to_string()).into());
+        }
+    }
+    
+    // Handle shim creation
+    let config = load_config().unwrap_or_default();
+    if config.behavior.create_shims {
+        if binaries.is_empty() && config.behavior.verbose {
+            println!("   ℹ Could not locate binary for shim creation");
+        }
+        let registry = ShimRegistry::load().unwrap_or_default();
+        for path in &binaries {
+            let name = if Some(path) == primary.as_ref() { command.clone() } else { binary_name(path) };
+            // Leave pinned and other packages' shims alone; only the main command may take over a name
+            let existing = registry.get(&name);
+            if existing.is_some_and(|shim| shim.pinned || (name != command && shim.target != *path)) {
+                continue;
+            }
+            match create_shim_internal(&name, path, tx.backend.name()) {
+                Ok(shim_path) => {
+                    println!("   ✓ Created shim: {}", shim_path.display());
+                    tx.shims.push(name);
+                }
+                Err(e) => eprintln!("   ⚠ Shim creation failed: {}", e),
+            }
+        }
+    }
+    
+    // Remember what we installed so update/uninstall can find it later
+    let mut record = InstallRecord::new(package, tx.backend.name());
+    record.requested = tx.requested.clone();
+    record.version = installed_version(tx.backend.as_ref(), package);
+    // Main executable first, so lockfiles and verification can rely on it
+    record.binaries = primary.iter()
+        .chain(binaries.iter().filter(|path| Some(*path) != primary.as_ref()))
+        .cloned()
+        .collect();
+    record.shims = tx.shims.clone();
+    record.commands = commands.iter().map(ToString::to_string).collect();
+    tx.journal_id = journal_change(
+        JournalAction::Install, package, tx.backend.name(), before, record.version.clone(), undoes,
+    );
+    
+    let saved = StateDb::load().and_then(|mut state| {
+        state.record(record);
+        state.save()
+    });
+    match saved {
+        Ok(()) => tx.recorded = true,
+        Err(e) => eprintln!("   ⚠ Could not record install state: {}", e),
+    }
+    
+    Ok(tx)
+}
+
+/// Whether a package is already installed, and at which version
+///
+/// A backend that can't list its packages counts as having it, so a
+/// rollback never removes something the user may have installed themselves.
+fn existing_install(backend: &dyn Backend, package: &str) -> (bool, Option<String>) {
+    match backend.list_installed() {
+        Ok(installed) => match installed.into_iter().find(|p| p.name == package) {
+            Some(found) => (true, found.version),
+            None => (false, None),
+        },
+        Err(_) => (true, None),
+    }
+}
+
+/// Version of an installed package as reported by its backend
+fn installed_version(backend: &dyn Backend, package: &str) -> Option<String> {
+    backend.list_installed().ok()?
+        .into_iter()
+        .find(|p| p.name == package)
+        .and_then(|p| p.version)
+}
+
+/// A manifest resolved for this system and diffed against installed packages
+struct ProjectPlan {
+    /// Manifest location
+    path: PathBuf,
+    /// Manifest entries for this OS
+    packages: Vec<ManifestPackage>,
+    /// Backend used for entries that don't pin one
+    default_backend: String,
+    /// What sync would do for each entry
+    steps: Vec<SyncStep>,
+}
+
+/// Load the nearest (or given) manifest and compare it with installed packages
+fn plan_project(manifest_path: Option<&Path>) -> Result<ProjectPlan, OneInstallError> {
+    let path = match manifest_path {
+        Some(path) => path.to_path_buf(),
+        None => find_manifest(&std::env::current_dir()?).ok_or_else(|| {
+            OneInstallError::InvalidInput(format!("No {} found in this directory or its parents", MANIFEST_FILE))
+        })?,
+    };
+    let manifest = Manifest::load(&path)?;
+    
+    let context = OsContext::detect();
+    let mut packages = manifest.resolve(context.os_type.family());
+    
+    let default_backend = if packages.iter().any(|p| p.backend.is_none()) {
+        get_backend(None)?.name().to_string()
+    } else {
+        String::new()
+    };
+    
+    // Check and install under each backend's own name (fd → fd-find on apt)
+    let aliases = load_aliases();
+    for package in &mut packages {
+        let backend = package.backend.as_deref().unwrap_or(&default_backend);
+        package.name = aliases.package_for(&package.name, backend);
+    }
+    
+    // Ask each involved backend once for what it has installed
+    let mut installed: HashMap<String, Vec<PackageResult>> = HashMap::new();
+    for package in &packages {
+        let name = package.backend.as_deref().unwrap_or(&default_backend);
+        if !installed.contains_key(name) {
+            let listing = get_backend(Some(name))?.list_installed().unwrap_or_else(|e| {
+                eprintln!("   Warning: {} listing failed: {}", name, e);
+                Vec::new()
+            });
+            installed.insert(name.to_string(), listing);
+        }
+    }
+    
+    let steps = plan_sync(&packages, &default_backend, &installed);
+    Ok(ProjectPlan { path, packages, default_backend, steps })
+}
+
+/// Bring the system in line with a `1install.toml` manifest
+///
+/// Only missing or mismatched packages are touched, and both are installed
+/// at the manifest's version requirement. If any install or upgrade
+/// fails, the packages this sync already installed are rolled back. With
+/// `locked`, missing packages are installed at the exact versions in
+/// `1install.lock` and any drift from the lockfile is an error.
+fn sync_manifest(manifest_path: Option<&Path>, locked: bool) -> Result<(), OneInstallError> {
+    let mut plan = plan_project(manifest_path)?;
+    println!("📄 Syncing {} ({} packages)\n", plan.path.display(), plan.packages.len());
+    
+    let lock = if locked {
+        let lock = load_lockfile(&plan.path)?;
+        lock.check_covers(&plan.packages, &plan.default_backend)?;
+        check_lock_drift(&plan.steps, &lock)?;
+        // Show (and install) the locked versions. Anything installed already
+        // passed the drift check, so it matches the lock exactly.
+        for step in &mut plan.steps {
+            step.package.version = lock.get(&step.package.id).map(|l| l.version.clone());
+            if step.action == SyncAction::Upgrade {
+                step.action = SyncAction::Satisfied;
+            }
+        }
+        Some(lock)
+    } else {
+        None
+    };
+    
+    let steps = plan.steps;
+    render_sync_plan(&steps);
+    
+    let pending = steps.iter().filter(|s| s.action != SyncAction::Satisfied).count();
+    if pending == 0 {
+        println!("\n✓ Everything in the manifest is already installed.");
+        return Ok(());
+    }
+    println!();
+    
+    let mut transactions: Vec<Transaction> = Vec::new();
+    let mut upgraded = 0;
+    
+    for step in &steps {
+        let locked_entry = lock.as_ref().and_then(|l| l.get(&step.package.id));
+        // The lock pins an exact version; otherwise the manifest's requirement applies
+        let version = match locked_entry {
+            Some(entry) => Some(VersionReq::Exact(entry.version.clone())),
+            None => step.package.version_req(),
+        };
+        let outcome = match step.action {
+            SyncAction::Satisfied => continue,
+            SyncAction::Install => get_backend(Some(&step.backend))
+                .and_then(|backend| install_in_transaction(
+                    &step.package.name,
+                    backend.into(),
+                    locked_entry.and_then(|l| l.sha256.as_deref()),
+                    version.as_ref(),
+                    None,
+                ))
+                .map(|tx| transactions.push(tx)),
+            SyncAction::Upgrade => {
+                // Upgrade only runs when the installed version misses the
+                // manifest's requirement, which may mean moving down
+                println!("🔄 Upgrading {}...", step.package.name);
+                get_backend(Some(&step.backend))
+                    .and_then(|backend| {
+                        match version {
+                            Some(ref version) => backend.install_matching(&step.package.name, version)?,
+                            None => backend.update(&step.package.name)?,
+                        }
+                        let after = installed_version(backend.as_ref(), &step.package.name);
+                        journal_change(JournalAction::Update, &step.package.name, backend.name(),
+                            step.installed.clone(), after, None);
+                        Ok(())
+                    })
+                    .map(|_| upgraded += 1)
+            }
+        };
+        
+        if let Err(e) = outcome {
+            eprintln!("   ❌ {} failed: {}", step.package.name, e);
+            for tx in transactions.iter().rev() {
+                let _ = tx.rollback();
+            }
+            return Err(e);
+        }
+    }
+    
+    if !is_dry_run() {
+        println!("\n✓ Synced: {} installed, {} upgraded, {} already up to date.",
+            transactions.len(), upgraded, steps.len() - pending);
+    }
+    Ok(())
+}
+
+/// Load the lockfile that sits next to a manifest
+fn load_lockfile(manifest: &Path) -> Result<Lockfile, OneInstallError> {
+    let path = Lockfile::path_for(manifest);
+    if !path.exists() {
+        return Err(OneInstallError::InvalidInput(format!(
+            "No {} next to {}; run '1i lock' first", LOCK_FILE, manifest.display()
+        )));
+    }
+    Lockfile::load(&path)
+}
+
+/// Fail if any installed package differs from its locked version or hash
+fn check_lock_drift(steps: &[SyncStep], lock: &Lockfile) -> Result<(), OneInstallError> {
+    let mut drift = Vec::new();
+    
+    for step in steps {
+        let (Some(installed), Some(locked)) = (&step.installed, lock.get(&step.package.id)) else {
+            continue;
+        };
+        if *installed != locked.version {
+            drift.push(format!("{} is {} but locked at {}", step.package.name, installed, locked.version));
+            continue;
+        }
+        if let Some(ref hash) = locked.sha256 {
+            let binary = locked.binary.clone()
+                .filter(|path| path.exists())
+                .or_else(|| recorded_binary(&step.package.name, &step.backend));
+            match binary {
+                Some(path) => verify_file_hash(&path, hash)?,
+                None => return Err(VerificationError::BinaryNotFound(step.package.name.clone()).into()),
+            }
+        }
+    }
+    
+    if drift.is_empty() {
+        Ok(())
+    } else {
+        Err(OneInstallError::LockMismatch(drift.join(", ")))
+    }
+}
+
+/// Record the exact installed version and binary hash of every manifest package
+fn lock_manifest(manifest_path: Option<&Path>) -> Result<(), OneInstallError> {
+    let plan = plan_project(manifest_path)?;
+    println!("🔒 Locking {} ({} packages)\n", plan.path.display(), plan.packages.len());
+    
+    let state = StateDb::load().unwrap_or_default();
+    let mut locked = Vec::new();
+    for step in &plan.steps {
+        let version = match (step.action, &step.installed) {
+            (SyncAction::Install, _) => {
+                return Err(OneInstallError::PackageNotFound {
+                    package: step.package.name.clone(),
+                    backend: Some(step.backend.clone()),
+                });
+            }
+            (SyncAction::Upgrade, _) => {
+                return Err(OneInstallError::InvalidInput(format!(
+                    "{} does not match the manifest; run '1i sync' first", step.package.name
+                )));
+            }
+            (SyncAction::Satisfied, None) => {
+                return Err(OneInstallError::InvalidInput(format!(
+                    "{} does not report an installed version, so it cannot be locked", step.backend
+                )));
+            }
+            (SyncAction::Satisfied, Some(version)) => version.clone(),
+        };
+        
+        let binary = recorded_binary(&step.package.name, &step.backend);
+        let sha256 = match binary {
+            Some(ref path) => Some(compute_file_hash(path)?),
+            None => None,
+        };
+        // Only packages installed through 1install have a known install time
+        let installed_at = state.find(&step.package.name).into_iter()
+            .find(|record| record.name == step.package.name && backend_matches(&record.backend, &step.backend))
+            .map(|record| record.installed_at);
+        
+        println!("   {} {} ({}){}", step.package.name, version, step.backend,
+            if sha256.is_some() { "" } else { " — no binary found, hash not recorded" });
+        
+        locked.push(LockedPackage {
+            id: step.package.id.clone(),
+            name: step.package.name.clone(),
+            backend: step.backend.clone(),
+            version,
+            sha256,
+            binary,
+            installed_at,
+        });
+    }
+    
+    let lock_path = Lockfile::path_for(&plan.path);
+    Lockfile::new(locked).save(&lock_path)?;
+    println!("\n✓ Wrote {}", lock_path.display());
+    Ok(())
+}
+
+/// Update a package, or move it to the version in its spec
+fn update_package(spec: &PackageSpec, backend_name: Option<&str>) -> Result<(), OneInstallError> {
+    println!("🔍 Detecting system...");
+    let backend = get_installed_backend(&spec.name, spec.backend_or(backend_name)?)?;
+    let package = load_aliases().package_for(&spec.name, backend.name());
+    let package = package.as_str();
+    
+    println!("   Backend: {}", backend.name());
+    println!("   Status: ✓ Available\n");
+    
+    let before = if is_dry_run() { None } else { installed_version(backend.as_ref(), package) };
+    match spec.version {
+        Some(ref version) => {
+            println!("🔄 Moving {} to {}...", package, version);
+            backend.install_matching(package, version)?;
+        }
+        None => {
+            println!("🔄 Updating {}...", package);
+            backend.update(package)?;
+        }
+    }
+    
+    if is_dry_run() {
+        return Ok(());
+    }
+    
+    let after = installed_version(backend.as_ref(), package);
+    journal_change(JournalAction::Update, package, backend.name(), before, after.clone(), None);
+    
+    let mut state = StateDb::load()?;
+    if let Some(record) = state.get_mut(package, backend.name()) {
+        record.version = after;
+        record.updated_at = Some(chrono::Utc::now());
+        state.save()?;
+    }
+    
+    println!("\n✓ {} updated successfully!", package);
+    Ok(())
+}
+
+/// Uninstall a package
+fn uninstall_package(package: &str, backend_name: Option<&str>) -> Result<(), OneInstallError> {
+    println!("🔍 Detecting system...");
+    let backend = get_installed_backend(package, backend_name)?;
+    let package = load_aliases().package_for(package, backend.name());
+    let package = package.as_str();
+    
+    println!("   Backend: {}", backend.name());
+    println!("   Status: ✓ Available\n");
+    
+    println!("🗑️ Uninstalling {}...", package);
+    let before = if is_dry_run() { None } else { installed_version(backend.as_ref(), package) };
+    backend.uninstall(package)?;
+    
+    if is_dry_run() {
+        return Ok(());
+    }
+    
+    journal_change(JournalAction::Uninstall, package, backend.name(), before, None, None);
+    forget_package(package, backend.name())?;
+    
+    println!("\n✓ {} uninstalled successfully!", package);
+    Ok(())
+}
+
+/// Drop an uninstalled package's state record and shims
+fn forget_package(package: &str, backend_name: &str) -> Result<(), OneInstallError> {
+    let mut state = StateDb::load()?;
+    let record = state.remove(package, backend_name);
+    if record.is_some() {
+        state.save()?;
+    }
+    
+    // Remove the package's shims (those we recorded, plus one named after it)
+    let mut shim_names: Vec<String> = record.map(|r| r.shims).unwrap_or_default();
+    if !shim_names.iter().any(|name| name == package) {
+        shim_names.push(package.to_string());
+    }
+    let mut registry = ShimRegistry::load()?;
+    let mut removed = 0;
+    for name in &shim_names {
+        if registry.remove(name).is_some() {
+            remove_shim(name);
+            removed += 1;
+        }
+    }
+    if removed > 0 {
+        println!("   ✓ Removed {} associated shim(s)", removed);
+        registry.save()?;
+    }
+    Ok(())
+}
+
+/// Show the most recent journal entries, optionally for one package
+fn show_history(package: Option<&str>, limit: usize) -> Result<(), OneInstallError> {
+    let journal = Journal::load()?;
+    let entries: Vec<JournalEntry> = journal.entries().iter()
+        .filter(|e| package.is_none_or(|name| e.package == name))
+        .cloned()
+        .collect();
+    let shown = &entries[entries.len().saturating_sub(limit)..];
+    
+    if !is_human_output() {
+        return emit_list(shown);
+    }
+    render_history(shown, &StateDb::load().unwrap_or_default());
+    Ok(())
+}
+
+/// Reverse a journaled change, or the most recent one still in effect
+///
+/// Installs are uninstalled, uninstalls are reinstalled (at the old version
+/// where the backend can pin one) and updates are downgraded, which only
+/// works on backends that can install a specific version.
+fn undo_change(id: Option<u64>) -> Result<(), OneInstallError> {
+    let journal = Journal::load()?;
+    let entry = match id {
+        Some(id) => journal.get(id)
+            .ok_or_else(|| OneInstallError::InvalidInput(format!("No journal entry #{}; see '1i history'", id)))?,
+        None => journal.last_undoable()
+            .ok_or_else(|| OneInstallError::InvalidInput("Nothing to undo".to_string()))?,
+    }.clone();
+    
+    if let Some(original) = entry.undoes {
+        return Err(OneInstallError::InvalidInput(format!(
+            "#{} already reverses #{}; install or uninstall {} directly instead", entry.id, original, entry.package
+        )));
+    }
+    if let Some(undo) = journal.undone_by(entry.id) {
+        return Err(OneInstallError::InvalidInput(format!("#{} was already undone by #{}", entry.id, undo.id)));
+    }
+    
+    println!("↩️ Undoing #{}: {} {} ({})\n", entry.id, entry.action, entry.package, entry.backend);
+    let backend = get_backend(Some(&entry.backend))?;
+    let package = entry.package.as_str();
+    
+    match (entry.action, entry.before.as_deref()) {
+        (JournalAction::Uninstall, before) => {
+            let backend: Arc<dyn Backend> = backend.into();
+            let before = before.map(|v| VersionReq::Exact(v.to_string()));
+            match install_in_transaction(package, backend.clone(), None, before.as_ref(), Some(entry.id)) {
+                Err(OneInstallError::Unsupported(reason)) => {
+                    println!("   ⚠ {}; reinstalling the latest version instead", reason);
+                    install_in_transaction(package, backend, None, None, Some(entry.id))?;
+                }
+                result => {
+                    result?;
+                }
+            }
+        }
+        (JournalAction::Install, None) => {
+            println!("🗑️ Uninstalling {}...", package);
+            backend.uninstall(package)?;
+            if !is_dry_run() {
+                journal_change(JournalAction::Uninstall, package, backend.name(), entry.after.clone(), None, Some(entry.id));
+                forget_package(package, backend.name())?;
+            }
+        }
+        (JournalAction::Install | JournalAction::Update, Some(before)) => {
+            if entry.after.as_deref() == Some(before) {
+                println!("   {} was already at {}; nothing to revert.", package, before);
+                journal_change(entry.action, package, backend.name(), entry.after.clone(), entry.after.clone(), Some(entry.id));
+            } else {
+                println!("⏪ Reverting {} to {}...", package, before);
+                backend.install_version(package, before)?;
+                if !is_dry_run() {
+                    let after = installed_version(backend.as_ref(), package);
+                    journal_change(JournalAction::Update, package, backend.name(), entry.after.clone(), after.clone(), Some(entry.id));
+                    let mut state = StateDb::load()?;
+                    if let Some(record) = state.get_mut(package, backend.name()) {
+                        record.version = after;
+                        record.updated_at = Some(chrono::Utc::now());
+                        state.save()?;
+                    }
+                }
+            }
+        }
+        (JournalAction::Update, None) => {
+            return Err(OneInstallError::Unsupported(format!(
+                "#{} did not record the version {} had before, so it cannot be reverted", entry.id, package
+            )));
+        }
+    }
+    
+    if !is_dry_run() {
+        println!("\n✓ Undid #{}", entry.id);
+    }
+    Ok(())
+}
+
+/// Pick the backend for an already-installed package
+///
+/// An explicit `--backend` wins; otherwise the state DB says which backend
+/// installed it, falling back to the system default.
+fn get_installed_backend(package: &str, backend_name: Option<&str>) -> Result<Box<dyn Backend>, OneInstallError> {
+    if backend_name.is_some() {
+        return get_backend(backend_name);
+    }
+    // State records the backend's own name, which may be an alias of what was typed
+    let state = StateDb::load()?;
+    let mut recorded = None;
+    for name in load_aliases().names_for(package) {
+        if let Some(backend) = state.backend_for(&name)? {
+            recorded = Some(backend);
+            break;
+        }
+    }
+    match recorded {
+        Some(recorded) => {
+            println!("   (installed via {}, per 1install state)", recorded);
+            get_backend(Some(recorded))
+        }
+        None => get_backend(None),
+    }
+}
+
+/// Bundled aliases plus the user's, or just the bundled ones if the user's file is broken
+fn load_aliases() -> AliasDb {
+    AliasDb::load().unwrap_or_else(|e| {
+        eprintln!("   Warning: ignoring {}: {}", AliasDb::user_path().display(), e);
+        AliasDb::bundled()
+    })
+}
+
+/// Helper to get a backend: the named one, or the highest-priority enabled one
+fn get_backend(backend_name: Option<&str>) -> Result<Box<dyn Backend>, OneInstallError> {
+    BackendResolver::from_config().resolve(backend_name)
+}
+
+/// Get enabled backends by priority, optionally narrowed to a single named backend
+fn get_matching_backends(backend_filter: Option<&str>) -> Result<Vec<Box<dyn Backend>>, OneInstallError> {
+    let resolver = BackendResolver::from_config();
+    match backend_filter {
+        Some(name) => resolver.resolve(Some(name)).map(|backend| vec![backend]),
+        None => Ok(resolver.available()),
+    }
+}
+
+/// Executables a package installed, plus the one its command runs
+///
+/// Asks the backend first, falling back to a PATH lookup for backends that
+/// can't list a package's files.
+fn package_binaries(backend: &dyn Backend, package: &str, aliases: &AliasDb) -> (Vec<PathBuf>, Option<PathBuf>) {
+    let expected = aliases.binary_for(package, backend.name());
+    let mut binaries = backend.binaries(package).unwrap_or_default();
+    let mut primary = binaries.iter().find(|path| binary_name(path) == expected).cloned();
+    
+    // Metapackages (python3) may not own their command, but it usually sits
+    // next to their executables; a lone executable under another name
+    // (ripgrep's rg) is the command
+    if primary.is_none() {
+        primary = binaries.iter()
+            .filter_map(|path| Some(path.parent()?.join(&expected)))
+            .find(|path| path.is_file())
+            .or_else(|| find_binary(&expected));
+        match primary {
+            Some(ref path) if !binaries.contains(path) => binaries.insert(0, path.clone()),
+            Some(_) => {}
+            None if binaries.len() == 1 => primary = binaries.first().cloned(),
+            None => {}
+        }
+    }
+    (binaries, primary)
+}
+
+/// The main binary recorded for an installed package, else a PATH lookup
+fn recorded_binary(package: &str, backend: &str) -> Option<PathBuf> {
+    let state = StateDb::load().ok()?;
+    state.find(package).into_iter()
+        .filter(|record| backend_matches(&record.backend, backend))
+        .find_map(|record| record.binaries.first().filter(|path| path.exists()).cloned())
+        .or_else(|| find_binary(package))
+}
+
+/// Command name of an executable (without `.exe`/`.cmd` on Windows)
+fn binary_name(path: &Path) -> String {
+    let name = if cfg!(windows) { path.file_stem() } else { path.file_name() };
+    name.map(|n| n.to_string_lossy().into_owned()).unwrap_or_default()
+}
+
+/// Find a binary in PATH, skipping 1install's own shims
+fn find_binary(name: &str) -> Option<PathBuf> {
+    let output = if cfg!(windows) {
+        std::process::Command::new("where").arg(name).output().ok()?
+    } else {
+        std::process::Command::new("which").args(["-a", name]).output().ok()?
+    };
+    if !output.status.success() {
+        return None;
+    }
+    let shim_dir = get_shim_dir();
+    String::from_utf8_lossy(&output.stdout).lines()
+        .map(|line| PathBuf::from(line.trim()))
+        .find(|path| !path.starts_with(&shim_dir))
+}
+
+/// Internal shim creation logic
+fn create_shim_internal(name: &str, target: &Path, backend_name: &str) -> Result<PathBuf, OneInstallError> {
+    let mut registry = ShimRegistry::load()?;
+    let shim_path = shims::create_shim(registry.add(name.to_string(), target.to_path_buf(), backend_name.to_string()))?;
+    registry.save()?;
+    Ok(shim_path)
+}
+
+/// List enabled backends in priority order
+fn list_backends() -> Result<(), OneInstallError> {
+    let backends = BackendResolver::from_config().available();
+    let names: Vec<&str> = backends.iter().map(|b| b.name()).collect();
+    if !is_human_output() {
+        let entries: Vec<BackendEntry> = names.into_iter().map(|name| BackendEntry { name }).collect();
+        return emit_list(&entries);
+    }
+    render_backends(&names);
+    Ok(())
+}
+
+/// Machine-readable row for `1i backends`
+#[derive(Serialize)]
+struct BackendEntry<'a> {
+    name: &'a str,
+}
+
+/// Report that no backend is available (an empty list in machine formats)
+fn no_backends_available<T: Serialize>() -> Result<(), OneInstallError> {
+    if !is_human_output() {
+        return emit_list::<T>(&[]);
+    }
+    println!("No package managers available on this system.");
+    Ok(())
+}
+
+/// Handle config subcommands
+fn handle_config(action: ConfigAction) -> Result<(), OneInstallError> {
+    match action {
+        ConfigAction::Get { key } => {
+            let config = load_config()?;
+            match config.get(&key) {
+                Some(value) => println!("{} = {}", key, value),
+                None => eprintln!("Unknown config key: {}", key),
+            }
+        }
+        ConfigAction::Set { key, value } => {
+            let mut config = load_config()?;
+            config.set(&key, &value)?;
+            save_config(&config)?;
+            println!("✓ Set {} = {}", key, value);
         }
         ConfigAction::List => {
             let config = load_config()?;
+            if !is_human_output() {
+                return emit_one(&config);
+            }
             println!("Configuration:\n");
-            for key in Config::list_keys() {
-                if let Some(value) = config.get(key) {
+            let keys = Config::list_keys().into_iter().map(String::from).chain(config.choice_keys());
+            for key in keys {
+                if let Some(value) = config.get(&key) {
                     println!("  {} = {}", key, value);
                 }
             }
@@ -383,20 +1882,35 @@ fn handle_config(action: ConfigAction) -> Result<(), Box<dyn std::error::Error>>
 }
 
 /// Handle shims subcommands
-fn handle_shims(action: ShimsAction) -> Result<(), Box<dyn std::error::Error>> {
+fn handle_shims(action: ShimsAction) -> Result<(), OneInstallError> {
     match action {
         ShimsAction::List => {
             let registry = ShimRegistry::load()?;
+            if !is_human_output() {
+                let mut shims: Vec<&ShimEntry> = registry.list().collect();
+                shims.sort_by(|a, b| a.name.cmp(&b.name));
+                return emit_list(&shims);
+            }
             if registry.is_empty() {
                 println!("No shims registered.");
             } else {
                 println!("Registered shims ({}):\n", registry.len());
                 for shim in registry.list() {
-                    println!("  {} → {} ({})", 
+                    println!("  {} → {} ({}){}", 
                         shim.name, 
                         shim.target.display(),
-                        shim.installed_by
+                        shim.installed_by,
+                        if shim.pinned { " 📌" } else { "" }
                     );
+                    for (key, value) in &shim.env {
+                        println!("      env {}={}", key, value);
+                    }
+                    if !shim.args.is_empty() {
+                        println!("      args {}", shim.args.join(" "));
+                    }
+                    if shim.cwd != WorkingDir::Inherit {
+                        println!("      cwd {}", shim.cwd);
+                    }
                 }
             }
         }
@@ -406,12 +1920,249 @@ fn handle_shims(action: ShimsAction) -> Result<(), Box<dyn std::error::Error>> {
         ShimsAction::Setup => {
             println!("{}", shims::get_path_instruction());
         }
+        ShimsAction::Refresh => {
+            let changes = refresh_all_shims()?;
+            if !is_human_output() {
+                return emit_list(&changes);
+            }
+            if changes.is_empty() {
+                println!("✓ All shims are up to date.");
+            } else {
+                render_shim_changes(&changes);
+            }
+        }
+        ShimsAction::Add { binary, name } => add_shim(&binary, name.as_deref())?,
+        ShimsAction::Remove { name } => remove_shim_by_name(&name)?,
+        ShimsAction::Rename { from, to } => rename_shim(&from, &to)?,
+        ShimsAction::Pin { name, unpin } => pin_shim(&name, !unpin)?,
+        ShimsAction::SetEnv { name, vars } => {
+            let vars = vars.iter().map(|var| parse_env_var(var)).collect::<Result<Vec<_>, _>>()?;
+            edit_shim(&name, |entry| {
+                for (key, value) in vars {
+                    match value {
+                        Some(value) => entry.env.insert(key, value),
+                        None => entry.env.remove(&key),
+                    };
+                }
+            })?;
+        }
+        ShimsAction::SetArgs { name, args } => edit_shim(&name, |entry| entry.args = args)?,
+        ShimsAction::SetCwd { name, cwd } => edit_shim(&name, |entry| entry.cwd = cwd)?,
+    }
+    Ok(())
+}
+
+/// Create a shim for a binary given by path or PATH lookup
+fn add_shim(binary: &str, name: Option<&str>) -> Result<(), OneInstallError> {
+    let target = if binary.contains(std::path::is_separator) {
+        let path = std::path::absolute(binary)?;
+        if !path.is_file() {
+            return Err(OneInstallError::InvalidInput(format!("{} is not a file", path.display())));
+        }
+        path
+    } else {
+        find_binary(binary)
+            .ok_or_else(|| OneInstallError::InvalidInput(format!("No binary named '{}' on PATH", binary)))?
+    };
+    let name = name.map_or_else(|| binary_name(&target), str::to_string);
+    
+    let registry = ShimRegistry::load()?;
+    check_new_shim_name(&registry, &name)?;
+    if !is_dry_run() {
+        create_shim_internal(&name, &target, "manual")?;
+    }
+    println!("✓ Created shim {} → {}", name, target.display());
+    Ok(())
+}
+
+/// Delete a shim and forget it everywhere it is recorded
+fn remove_shim_by_name(name: &str) -> Result<(), OneInstallError> {
+    let mut registry = ShimRegistry::load()?;
+    let entry = registry.remove(name).ok_or_else(|| unknown_shim(name))?;
+    if !is_dry_run() {
+        remove_shim(name);
+        registry.save()?;
+        update_recorded_shims(|shims| shims.retain(|shim| shim != name))?;
+    }
+    println!("✓ Removed shim {} (was → {})", name, entry.target.display());
+    Ok(())
+}
+
+/// Move a shim to a new name, keeping its target and pin
+fn rename_shim(from: &str, to: &str) -> Result<(), OneInstallError> {
+    let mut registry = ShimRegistry::load()?;
+    let target = registry.get(from).ok_or_else(|| unknown_shim(from))?.target.clone();
+    check_new_shim_name(&registry, to)?;
+    let entry = registry.rename(from, to).cloned().ok_or_else(|| unknown_shim(from))?;
+    if !is_dry_run() {
+        shims::create_shim(&entry)?;
+        remove_shim(from);
+        registry.save()?;
+        update_recorded_shims(|shims| {
+            for shim in shims.iter_mut().filter(|shim| *shim == from) {
+                *shim = to.to_string();
+            }
+        })?;
+    }
+    println!("✓ Renamed shim {} → {} (runs {})", from, to, target.display());
+    Ok(())
+}
+
+/// Pin a shim to its target, or let refresh retarget it again
+fn pin_shim(name: &str, pinned: bool) -> Result<(), OneInstallError> {
+    let mut registry = ShimRegistry::load()?;
+    let target = registry.set_pinned(name, pinned).ok_or_else(|| unknown_shim(name))?.target.clone();
+    if !is_dry_run() {
+        registry.save()?;
+    }
+    if pinned {
+        println!("📌 Pinned {} to {}", name, target.display());
+    } else {
+        println!("✓ Unpinned {}; refresh may retarget it", name);
+    }
+    Ok(())
+}
+
+/// Change a shim's settings and rewrite its script to match
+fn edit_shim(name: &str, edit: impl FnOnce(&mut ShimEntry)) -> Result<(), OneInstallError> {
+    let mut registry = ShimRegistry::load()?;
+    let entry = registry.get_mut(name).ok_or_else(|| unknown_shim(name))?;
+    edit(entry);
+    let entry = entry.clone();
+    if !is_dry_run() {
+        shims::create_shim(&entry)?;
+        registry.save()?;
+    }
+    
+    println!("✓ Updated shim {} → {}", name, entry.target.display());
+    for (key, value) in &entry.env {
+        println!("   env {}={}", key, value);
+    }
+    if !entry.args.is_empty() {
+        println!("   args {}", entry.args.join(" "));
+    }
+    println!("   cwd {}", entry.cwd);
+    Ok(())
+}
+
+/// Split `KEY=VAL` (an empty value unsets `KEY`)
+fn parse_env_var(var: &str) -> Result<(String, Option<String>), OneInstallError> {
+    let (key, value) = var.split_once('=')
+        .ok_or_else(|| OneInstallError::InvalidInput(format!("Expected KEY=VAL, got '{}'", var)))?;
+    let valid = key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(OneInstallError::InvalidInput(format!("'{}' is not a valid environment variable name", key)));
+    }
+    Ok((key.to_string(), (!value.is_empty()).then(|| value.to_string())))
+}
+
+/// Reject shim names that are taken or can't be a file name
+fn check_new_shim_name(registry: &ShimRegistry, name: &str) -> Result<(), OneInstallError> {
+    if name.is_empty() || name.contains(std::path::is_separator) || name.starts_with('.') {
+        return Err(OneInstallError::InvalidInput(format!("'{}' is not a valid shim name", name)));
+    }
+    if let Some(existing) = registry.get(name) {
+        return Err(OneInstallError::InvalidInput(format!(
+            "Shim '{}' already exists (→ {}); remove it first with '1i shims remove {}'",
+            name, existing.target.display(), name
+        )));
     }
     Ok(())
 }
 
+/// Error for a shim name the registry doesn't know
+fn unknown_shim(name: &str) -> OneInstallError {
+    OneInstallError::InvalidInput(format!("No shim named '{}' (see '1i shims list')", name))
+}
+
+/// Apply an edit to the shim names recorded for every installed package
+fn update_recorded_shims(edit: impl Fn(&mut Vec<String>)) -> Result<(), OneInstallError> {
+    let mut state = StateDb::load()?;
+    let packages: Vec<(String, String)> = state.list().map(|r| (r.name.clone(), r.backend.clone())).collect();
+    for (name, backend) in packages {
+        if let Some(record) = state.get_mut(&name, &backend) {
+            edit(&mut record.shims);
+        }
+    }
+    state.save()
+}
+
+/// Re-resolve every registered shim, rewriting or removing it as needed
+///
+/// The registry and install state are updated to match; in dry run nothing
+/// is written and the changes are only reported.
+fn refresh_all_shims() -> Result<Vec<ShimChange>, OneInstallError> {
+    let mut registry = ShimRegistry::load()?;
+    let mut state = StateDb::load()?;
+    let aliases = load_aliases();
+    let changes = refresh_shims(&mut registry, |entry| resolve_shim_target(entry, &state, &aliases))?;
+    if changes.is_empty() || is_dry_run() {
+        return Ok(changes);
+    }
+    registry.save()?;
+    
+    // Keep install records pointing at the same binaries and shims
+    let packages: Vec<(String, String)> = state.list().map(|r| (r.name.clone(), r.backend.clone())).collect();
+    for (name, backend) in packages {
+        let Some(record) = state.get_mut(&name, &backend) else { continue };
+        for change in &changes {
+            match (change.change, &change.target) {
+                (ShimChangeKind::Removed, _) => record.shims.retain(|shim| *shim != change.name),
+                (ShimChangeKind::Retargeted, Some(target)) => {
+                    for binary in record.binaries.iter_mut().filter(|b| **b == change.previous) {
+                        *binary = target.clone();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    state.save()?;
+    Ok(changes)
+}
+
+/// Refresh shims after packages changed, if `shims.auto_refresh` is on
+fn auto_refresh_shims() {
+    if is_dry_run() || !load_config().unwrap_or_default().shims.auto_refresh {
+        return;
+    }
+    match refresh_all_shims() {
+        Ok(changes) if is_human_output() => {
+            for change in changes {
+                match (change.change, change.target) {
+                    (ShimChangeKind::Retargeted, Some(target)) => {
+                        println!("   ↪ Shim {} now points at {}", change.name, target.display());
+                    }
+                    (ShimChangeKind::Removed, _) => {
+                        println!("   ✗ Removed shim {} ({} is gone)", change.name, change.previous.display());
+                    }
+                    _ => println!("   ✓ Regenerated shim {}", change.name),
+                }
+            }
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("   ⚠ Could not refresh shims: {}", e),
+    }
+}
+
+/// Where a shim's binary went, asking the package that created it first
+fn resolve_shim_target(entry: &ShimEntry, state: &StateDb, aliases: &AliasDb) -> Option<PathBuf> {
+    let wanted = binary_name(&entry.target);
+    let from_package = state.list()
+        .find(|record| record.shims.contains(&entry.name))
+        .and_then(|record| {
+            let backend = get_all_available_backends().into_iter().find(|b| backend_matches(b.name(), &record.backend))?;
+            let (binaries, primary) = package_binaries(backend.as_ref(), &record.name, aliases);
+            binaries.into_iter()
+                .find(|path| binary_name(path) == wanted)
+                .or(primary.filter(|_| entry.name == aliases.command_for(&record.name)))
+        });
+    from_package.or_else(|| find_binary(&wanted))
+}
+
 /// Handle 1install self-installation/bootstrapping
-fn handle_self_install() -> Result<(), Box<dyn std::error::Error>> {
+fn handle_self_install() -> Result<(), OneInstallError> {
     println!("🚀 Bootstrapping 1install...");
     
     // 1. Ensure shim directory exists
@@ -3,6 +3,7 @@
 //! This is the main entry point for the `1i` CLI tool.
 
 use oneinstall::cli::Cli;
+use oneinstall::telemetry::{TelemetryClient, TelemetryEvent};
 use clap::Parser;
 
 #[tokio::main]
@@ -11,6 +12,9 @@ async fn main() {
     
     if let Err(e) = oneinstall::run(cli).await {
         eprintln!("Error: {}", e);
-        std::process::exit(1);
+        TelemetryClient::track_event(TelemetryEvent::ErrorOccurred {
+            error_type: e.class().to_string(),
+        });
+        std::process::exit(e.exit_code());
     }
 }
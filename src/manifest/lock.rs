@@ -0,0 +1,168 @@
+//! `1install.lock`: exact versions and binary hashes for a manifest
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use crate::error::OneInstallError;
+use super::ManifestPackage;
+
+/// Lockfile name, written next to the manifest
+pub const LOCK_FILE: &str = "1install.lock";
+
+/// Current lockfile format version
+const LOCK_VERSION: u32 = 1;
+
+/// A lockfile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Format version
+    pub version: u32,
+    /// When `1i lock` wrote this file
+    pub generated_at: DateTime<Utc>,
+    /// Locked packages, in manifest order
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+/// One locked package
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    /// Manifest key
+    pub id: String,
+    /// Native package name
+    pub name: String,
+    /// Backend that installed it
+    pub backend: String,
+    /// Exact installed version
+    pub version: String,
+    /// SHA-256 of the package's main binary, if one was found
+    pub sha256: Option<String>,
+    /// Path of the hashed binary
+    pub binary: Option<PathBuf>,
+    /// When the package was installed, if known
+    pub installed_at: Option<DateTime<Utc>>,
+}
+
+impl Lockfile {
+    /// Create a lockfile for the given packages
+    pub fn new(packages: Vec<LockedPackage>) -> Self {
+        Self {
+            version: LOCK_VERSION,
+            generated_at: Utc::now(),
+            packages,
+        }
+    }
+
+    /// Path of the lockfile belonging to a manifest
+    pub fn path_for(manifest: &Path) -> PathBuf {
+        manifest.with_file_name(LOCK_FILE)
+    }
+
+    /// Load a lockfile from disk
+    pub fn load(path: &Path) -> Result<Self, OneInstallError> {
+        let content = fs::read_to_string(path)?;
+        let lock: Lockfile = toml::from_str(&content)?;
+        if lock.version > LOCK_VERSION {
+            return Err(OneInstallError::LockMismatch(format!(
+                "{} uses format version {}, this 1i understands up to {}",
+                path.display(), lock.version, LOCK_VERSION
+            )));
+        }
+        Ok(lock)
+    }
+
+    /// Save the lockfile to disk
+    pub fn save(&self, path: &Path) -> Result<(), OneInstallError> {
+        let content = toml::to_string_pretty(self)?;
+        fs::write(path, format!("# Generated by `1i lock`. Do not edit by hand.\n\n{}", content))?;
+        Ok(())
+    }
+
+    /// Find the locked entry for a manifest key
+    pub fn get(&self, id: &str) -> Option<&LockedPackage> {
+        self.packages.iter().find(|p| p.id == id)
+    }
+
+    /// Check that the lockfile covers exactly the resolved manifest
+    ///
+    /// `default_backend` is the backend used for packages the manifest
+    /// doesn't pin.
+    pub fn check_covers(&self, packages: &[ManifestPackage], default_backend: &str) -> Result<(), OneInstallError> {
+        let mut problems = Vec::new();
+
+        for package in packages {
+            let backend = package.backend.as_deref().unwrap_or(default_backend);
+            match self.get(&package.id) {
+                None => problems.push(format!("{} is not locked", package.id)),
+                Some(locked) if locked.name != package.name || locked.backend != backend => {
+                    problems.push(format!(
+                        "{} is locked as {}:{} but the manifest wants {}:{}",
+                        package.id, locked.backend, locked.name, backend, package.name
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        for locked in &self.packages {
+            if !packages.iter().any(|p| p.id == locked.id) {
+                problems.push(format!("{} is locked but no longer in the manifest", locked.id));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(OneInstallError::LockMismatch(format!("{}; run '1i lock' to update it", problems.join(", "))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locked(id: &str, backend: &str) -> LockedPackage {
+        LockedPackage {
+            id: id.to_string(),
+            name: id.to_string(),
+            backend: backend.to_string(),
+            version: "1.0".to_string(),
+            sha256: None,
+            binary: None,
+            installed_at: None,
+        }
+    }
+
+    fn wanted(id: &str, backend: Option<&str>) -> ManifestPackage {
+        ManifestPackage {
+            id: id.to_string(),
+            name: id.to_string(),
+            backend: backend.map(String::from),
+            version: None,
+        }
+    }
+
+    #[test]
+    fn test_check_covers_detects_drift() {
+        let lock = Lockfile::new(vec![locked("jq", "apt"), locked("prettier", "npm")]);
+
+        assert!(lock.check_covers(&[wanted("jq", None), wanted("prettier", Some("npm"))], "apt").is_ok());
+        assert!(lock.check_covers(&[wanted("jq", Some("brew")), wanted("prettier", Some("npm"))], "apt").is_err());
+        assert!(lock.check_covers(&[wanted("jq", None)], "apt").is_err());
+        assert!(lock.check_covers(&[wanted("jq", None), wanted("prettier", Some("npm")), wanted("fd", None)], "apt").is_err());
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let mut entry = locked("jq", "apt");
+        entry.sha256 = Some("ab".repeat(32));
+        let lock = Lockfile::new(vec![entry]);
+
+        let text = toml::to_string_pretty(&lock).unwrap();
+        let parsed: Lockfile = toml::from_str(&text).unwrap();
+
+        assert_eq!(parsed.packages, lock.packages);
+        assert!(text.contains("[[package]]"));
+    }
+}
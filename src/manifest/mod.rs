@@ -0,0 +1,9 @@
+//! Project manifest (`1install.toml`) describing the packages a project needs
+
+mod lock;
+mod project;
+mod sync;
+
+pub use lock::{Lockfile, LockedPackage, LOCK_FILE};
+pub use project::{Manifest, ManifestEntry, PackageDetails, PackageOverride, ManifestPackage, find_manifest, MANIFEST_FILE};
+pub use sync::{SyncAction, SyncStep, plan_sync};
@@ -0,0 +1,213 @@
+//! `1install.toml` parsing and per-OS resolution
+//!
+//! ```toml
+//! [packages]
+//! jq = "*"
+//! prettier = { backend = "npm" }
+//! ripgrep = { version = "14" }
+//!
+//! [packages.fd.os.linux]
+//! name = "fd-find"
+//! backend = "apt"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::backends::VersionReq;
+use crate::error::OneInstallError;
+
+/// Manifest file name looked up in the project directory
+pub const MANIFEST_FILE: &str = "1install.toml";
+
+/// A project manifest
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Packages keyed by their portable name
+    #[serde(default)]
+    pub packages: BTreeMap<String, ManifestEntry>,
+}
+
+/// A package entry: either a bare version requirement or a full table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ManifestEntry {
+    /// `jq = "1.7"` (`"*"` means any version)
+    Version(String),
+    /// `jq = { backend = "apt", version = "1.7" }`
+    Detailed(PackageDetails),
+}
+
+/// Full package entry
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackageDetails {
+    /// Native package name, if different from the manifest key
+    pub name: Option<String>,
+    /// Backend to install with (defaults to the system backend)
+    pub backend: Option<String>,
+    /// Required version prefix (`"*"` or empty means any version)
+    pub version: Option<String>,
+    /// Overrides keyed by OS family (`linux`, `macos`, `windows`)
+    pub os: BTreeMap<String, PackageOverride>,
+}
+
+/// Per-OS override of a package entry
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PackageOverride {
+    /// Native package name on this OS
+    pub name: Option<String>,
+    /// Backend to use on this OS
+    pub backend: Option<String>,
+    /// Version requirement on this OS
+    pub version: Option<String>,
+    /// Leave this package out on this OS
+    pub skip: bool,
+}
+
+/// A manifest entry resolved for the current OS
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ManifestPackage {
+    /// Manifest key
+    pub id: String,
+    /// Native package name to install
+    pub name: String,
+    /// Backend to install with, if pinned
+    pub backend: Option<String>,
+    /// Required version prefix, if any
+    pub version: Option<String>,
+}
+
+impl ManifestPackage {
+    /// The version requirement to install with
+    ///
+    /// A bare version is a prefix (`"14"` means any 14.x), matching
+    /// [`ManifestPackage::accepts`]; globs and ranges keep their meaning.
+    pub fn version_req(&self) -> Option<VersionReq> {
+        match VersionReq::parse(self.version.as_deref()?)? {
+            VersionReq::Exact(version) => Some(VersionReq::Prefix(version)),
+            other => Some(other),
+        }
+    }
+
+    /// Whether an installed version satisfies the requirement
+    pub fn accepts(&self, installed: Option<&str>) -> bool {
+        match (&self.version, installed) {
+            (None, _) => true,
+            // Unknown installed version: trust the backend
+            (Some(_), None) => true,
+            (Some(required), Some(installed)) => {
+                let installed = installed.trim_start_matches('v');
+                installed == required
+                    || installed.strip_prefix(required.as_str()).is_some_and(|rest| {
+                        rest.starts_with(['.', '-', '+', '~', '_'])
+                    })
+            }
+        }
+    }
+}
+
+impl Manifest {
+    /// Load a manifest from disk
+    pub fn load(path: &Path) -> Result<Self, OneInstallError> {
+        let content = fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    /// Parse manifest TOML
+    pub fn parse(content: &str) -> Result<Self, OneInstallError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Resolve every package for an OS family, applying overrides
+    pub fn resolve(&self, os_family: &str) -> Vec<ManifestPackage> {
+        self.packages.iter().filter_map(|(id, entry)| {
+            let details = match entry {
+                ManifestEntry::Version(version) => PackageDetails {
+                    version: Some(version.clone()),
+                    ..Default::default()
+                },
+                ManifestEntry::Detailed(details) => details.clone(),
+            };
+            let os = details.os.get(os_family).cloned().unwrap_or_default();
+            if os.skip {
+                return None;
+            }
+
+            Some(ManifestPackage {
+                id: id.clone(),
+                name: os.name.or(details.name).unwrap_or_else(|| id.clone()),
+                backend: os.backend.or(details.backend),
+                version: os.version.or(details.version)
+                    .filter(|v| !v.is_empty() && v != "*"),
+            })
+        }).collect()
+    }
+}
+
+/// Find `1install.toml` in `start` or the nearest parent directory
+pub fn find_manifest(start: &Path) -> Option<PathBuf> {
+    start.ancestors()
+        .map(|dir| dir.join(MANIFEST_FILE))
+        .find(|path| path.is_file())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = r#"
+        [packages]
+        jq = "*"
+        prettier = { backend = "npm" }
+        ripgrep = "14"
+
+        [packages.fd.os.linux]
+        name = "fd-find"
+        backend = "apt"
+
+        [packages.fd.os.windows]
+        skip = true
+    "#;
+
+    #[test]
+    fn test_resolve_applies_os_overrides() {
+        let manifest = Manifest::parse(EXAMPLE).unwrap();
+
+        let linux = manifest.resolve("linux");
+        let fd = linux.iter().find(|p| p.id == "fd").unwrap();
+        assert_eq!(fd.name, "fd-find");
+        assert_eq!(fd.backend.as_deref(), Some("apt"));
+        assert_eq!(linux.iter().find(|p| p.id == "jq").unwrap().version, None);
+
+        let windows = manifest.resolve("windows");
+        assert!(windows.iter().all(|p| p.id != "fd"));
+        assert_eq!(windows.len(), 3);
+    }
+
+    #[test]
+    fn test_version_prefix_matching() {
+        let manifest = Manifest::parse(EXAMPLE).unwrap();
+        let rg = manifest.resolve("linux").into_iter().find(|p| p.id == "ripgrep").unwrap();
+
+        assert!(rg.accepts(Some("14.1.0")));
+        assert!(rg.accepts(Some("14")));
+        assert!(!rg.accepts(Some("13.0.0")));
+        assert!(!rg.accepts(Some("140.0")));
+        assert!(rg.accepts(None));
+    }
+
+    #[test]
+    fn test_version_req() {
+        let manifest = Manifest::parse(EXAMPLE).unwrap();
+        let linux = manifest.resolve("linux");
+        let rg = linux.iter().find(|p| p.id == "ripgrep").unwrap();
+        assert_eq!(rg.version_req(), Some(VersionReq::Prefix("14".into())));
+        assert_eq!(linux.iter().find(|p| p.id == "jq").unwrap().version_req(), None);
+
+        let ranged = ManifestPackage { version: Some("^8.50".into()), ..rg.clone() };
+        assert_eq!(ranged.version_req(), Some(VersionReq::Range("^8.50".into())));
+    }
+}
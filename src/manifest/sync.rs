@@ -0,0 +1,95 @@
+//! Diffing a resolved manifest against installed packages
+
+use serde::Serialize;
+use std::collections::HashMap;
+use crate::search::PackageResult;
+use super::ManifestPackage;
+
+/// What `1i sync` will do for one package
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SyncAction {
+    /// Not installed yet
+    Install,
+    /// Installed, but the version does not match the manifest
+    Upgrade,
+    /// Already installed at an acceptable version
+    Satisfied,
+}
+
+/// One line of the sync plan
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncStep {
+    /// The manifest entry
+    pub package: ManifestPackage,
+    /// Backend that will handle it
+    pub backend: String,
+    /// Currently installed version, if installed
+    pub installed: Option<String>,
+    /// Planned action
+    pub action: SyncAction,
+}
+
+/// Compare manifest packages with what each backend reports as installed
+///
+/// `installed` maps backend names to their `list_installed` output; packages
+/// without a pinned backend are assigned to `default_backend`.
+pub fn plan_sync(
+    packages: &[ManifestPackage],
+    default_backend: &str,
+    installed: &HashMap<String, Vec<PackageResult>>,
+) -> Vec<SyncStep> {
+    packages.iter().map(|package| {
+        let backend = package.backend.clone().unwrap_or_else(|| default_backend.to_string());
+        let current = installed.get(&backend)
+            .and_then(|list| list.iter().find(|p| p.name == package.name));
+
+        let action = match current {
+            None => SyncAction::Install,
+            Some(current) if !package.accepts(current.version.as_deref()) => SyncAction::Upgrade,
+            Some(_) => SyncAction::Satisfied,
+        };
+
+        SyncStep {
+            package: package.clone(),
+            backend,
+            installed: current.and_then(|p| p.version.clone()),
+            action,
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(name: &str, backend: Option<&str>, version: Option<&str>) -> ManifestPackage {
+        ManifestPackage {
+            id: name.to_string(),
+            name: name.to_string(),
+            backend: backend.map(String::from),
+            version: version.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_plan_sync() {
+        let packages = vec![
+            package("jq", None, None),
+            package("ripgrep", None, Some("14")),
+            package("prettier", Some("npm"), None),
+        ];
+        let mut installed = HashMap::new();
+        installed.insert("apt".to_string(), vec![
+            PackageResult::new("jq".into(), "apt".into()).with_version("1.7.1"),
+            PackageResult::new("ripgrep".into(), "apt".into()).with_version("13.0.0"),
+        ]);
+
+        let steps = plan_sync(&packages, "apt", &installed);
+        let actions: Vec<SyncAction> = steps.iter().map(|s| s.action).collect();
+
+        assert_eq!(actions, vec![SyncAction::Satisfied, SyncAction::Upgrade, SyncAction::Install]);
+        assert_eq!(steps[2].backend, "npm");
+        assert_eq!(steps[1].installed.as_deref(), Some("13.0.0"));
+    }
+}
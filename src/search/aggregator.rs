@@ -1,9 +1,14 @@
 //! Search aggregator for federated package discovery
 
+use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::time::Duration;
+use crate::backends::backend_matches;
+use crate::aliases::AliasDb;
+use crate::config::BackendConfig;
 
 /// A search result from a package manager
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PackageResult {
     /// Package name
     pub name: String,
@@ -46,6 +51,14 @@ impl PackageResult {
         self.score = score;
         self
     }
+
+    /// Whether the backend reported anything beyond the name
+    ///
+    /// A bare name is sometimes just the query echoed back (pip does this
+    /// when its index is unreachable) rather than a package that exists.
+    pub fn has_details(&self) -> bool {
+        self.version.is_some() || self.description.is_some()
+    }
 }
 
 impl PartialEq for PackageResult {
@@ -69,21 +82,71 @@ impl Ord for PackageResult {
     }
 }
 
-/// Backend priority for ranking results
-const BACKEND_PRIORITY: &[&str] = &["apt", "winget", "brew", "snap", "npm", "pip"];
+/// How a single backend's search ended
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchOutcome {
+    /// Finished with this many results
+    Completed(usize),
+    /// Returned an error
+    Failed(String),
+    /// Killed after exceeding the configured timeout
+    TimedOut,
+}
+
+/// Per-backend summary of a federated search
+#[derive(Debug, Clone)]
+pub struct BackendSearchReport {
+    /// Backend name
+    pub backend: String,
+    /// How the search ended
+    pub outcome: SearchOutcome,
+    /// Wall-clock time spent on this backend
+    pub elapsed: Duration,
+    /// Whether the results came from the on-disk cache
+    pub cached: bool,
+}
+
+impl BackendSearchReport {
+    /// Whether this backend failed to produce results
+    pub fn is_problem(&self) -> bool {
+        !matches!(self.outcome, SearchOutcome::Completed(_))
+    }
+}
 
 /// Search aggregator collects results from multiple backends
-pub struct SearchAggregator;
+pub struct SearchAggregator {
+    /// Backend names, highest priority first
+    priority: Vec<String>,
+    /// Names that count as an exact match for a query
+    aliases: AliasDb,
+}
+
+impl Default for SearchAggregator {
+    fn default() -> Self {
+        Self::new(BackendConfig::default().priority)
+    }
+}
 
 impl SearchAggregator {
+    /// Create an aggregator that ranks backends in the given order
+    pub fn new(priority: Vec<String>) -> Self {
+        Self { priority, aliases: AliasDb::default() }
+    }
+
+    /// Also treat a query's aliases (`fd` → `fd-find`) as exact matches
+    pub fn with_aliases(mut self, aliases: AliasDb) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
     /// Calculate the score for a package result
-    pub fn calculate_score(query: &str, result: &mut PackageResult) {
+    pub fn calculate_score(&self, query: &str, result: &mut PackageResult) {
         let mut score: f32 = 0.0;
         let query_lower = query.to_lowercase();
         let name_lower = result.name.to_lowercase();
 
         // Exact match bonus (highest priority)
-        if name_lower == query_lower {
+        if self.is_exact_match(query, result) {
             score += 100.0;
         }
         // Starts with query bonus
@@ -96,9 +159,9 @@ impl SearchAggregator {
         }
 
         // Backend priority bonus
-        if let Some(pos) = BACKEND_PRIORITY.iter().position(|&b| b == result.source) {
+        if let Some(pos) = self.priority.iter().position(|b| backend_matches(b, &result.source)) {
             // Earlier in list = higher priority = more bonus
-            score += (BACKEND_PRIORITY.len() - pos) as f32 * 5.0;
+            score += (self.priority.len() - pos) as f32 * 5.0;
         }
 
         // Has version bonus (packages with version info are more reliable)
@@ -114,11 +177,18 @@ impl SearchAggregator {
         result.score = score;
     }
 
+    /// Whether a result's name is the query or one of its aliases (ignoring case)
+    pub fn is_exact_match(&self, query: &str, result: &PackageResult) -> bool {
+        let name = result.name.to_lowercase();
+        name == query.to_lowercase()
+            || self.aliases.names_for(query).iter().any(|alias| alias.to_lowercase() == name)
+    }
+
     /// Rank and sort results
-    pub fn rank_results(query: &str, results: &mut [PackageResult]) {
+    pub fn rank_results(&self, query: &str, results: &mut [PackageResult]) {
         // Calculate scores
         for result in results.iter_mut() {
-            Self::calculate_score(query, result);
+            self.calculate_score(query, result);
         }
 
         // Sort by score (descending)
@@ -142,8 +212,9 @@ mod tests {
         let mut exact = PackageResult::new("python".to_string(), "apt".to_string());
         let mut partial = PackageResult::new("python3".to_string(), "apt".to_string());
 
-        SearchAggregator::calculate_score(query, &mut exact);
-        SearchAggregator::calculate_score(query, &mut partial);
+        let aggregator = SearchAggregator::default();
+        aggregator.calculate_score(query, &mut exact);
+        aggregator.calculate_score(query, &mut partial);
 
         assert!(exact.score > partial.score);
     }
@@ -154,12 +225,38 @@ mod tests {
         let mut apt_result = PackageResult::new("git".to_string(), "apt".to_string());
         let mut npm_result = PackageResult::new("git".to_string(), "npm".to_string());
 
-        SearchAggregator::calculate_score(query, &mut apt_result);
-        SearchAggregator::calculate_score(query, &mut npm_result);
+        let aggregator = SearchAggregator::default();
+        aggregator.calculate_score(query, &mut apt_result);
+        aggregator.calculate_score(query, &mut npm_result);
 
         assert!(apt_result.score > npm_result.score);
     }
 
+    #[test]
+    fn test_configured_priority() {
+        let aggregator = SearchAggregator::new(vec!["npm".to_string(), "apt".to_string()]);
+        let mut apt_result = PackageResult::new("git".to_string(), "apt".to_string());
+        let mut npm_result = PackageResult::new("git".to_string(), "npm".to_string());
+
+        aggregator.calculate_score("git", &mut apt_result);
+        aggregator.calculate_score("git", &mut npm_result);
+
+        assert!(npm_result.score > apt_result.score);
+    }
+
+    #[test]
+    fn test_aliases_match_exactly() {
+        let aggregator = SearchAggregator::default().with_aliases(AliasDb::bundled());
+        let mut fd_find = PackageResult::new("fd-find".to_string(), "apt".to_string());
+        let mut fdupes = PackageResult::new("fdupes".to_string(), "apt".to_string());
+
+        aggregator.calculate_score("fd", &mut fd_find);
+        aggregator.calculate_score("fd", &mut fdupes);
+
+        assert!(aggregator.is_exact_match("fd", &fd_find));
+        assert!(fd_find.score > fdupes.score);
+    }
+
     #[test]
     fn test_ranking() {
         let mut results = vec![
@@ -168,7 +265,7 @@ mod tests {
             PackageResult::new("python3".to_string(), "apt".to_string()),
         ];
 
-        SearchAggregator::rank_results("python", &mut results);
+        SearchAggregator::default().rank_results("python", &mut results);
 
         assert_eq!(results[0].name, "python");  // Exact match first
     }
@@ -0,0 +1,136 @@
+//! Persistent cache of per-backend search results
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use crate::config::get_data_dir;
+use crate::error::OneInstallError;
+use super::PackageResult;
+
+/// Results one backend returned for one query
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheEntry {
+    /// Backend that produced the results
+    pub backend: String,
+    /// Normalized search query
+    pub query: String,
+    /// When the backend was last asked
+    pub cached_at: DateTime<Utc>,
+    /// Results as returned by the backend
+    pub results: Vec<PackageResult>,
+}
+
+impl CacheEntry {
+    /// How long ago this entry was written
+    pub fn age(&self) -> Duration {
+        (Utc::now() - self.cached_at).to_std().unwrap_or_default()
+    }
+}
+
+/// On-disk search cache keyed by (backend, query)
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchCache {
+    entries: Vec<CacheEntry>,
+}
+
+impl SearchCache {
+    /// Load the cache from disk, starting empty if it is missing
+    pub fn load() -> Result<Self, OneInstallError> {
+        let path = Self::cache_path();
+
+        if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            let cache: SearchCache = toml::from_str(&content)?;
+            Ok(cache)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Save the cache to disk
+    pub fn save(&self) -> Result<(), OneInstallError> {
+        let path = Self::cache_path();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = toml::to_string_pretty(self)?;
+        fs::write(&path, content)?;
+        Ok(())
+    }
+
+    /// Get the cache file path
+    fn cache_path() -> PathBuf {
+        get_data_dir().join("search-cache.toml")
+    }
+
+    /// Look up cached results, ignoring entries older than `max_age` (if given)
+    pub fn get(&self, backend: &str, query: &str, max_age: Option<Duration>) -> Option<&CacheEntry> {
+        let query = normalize(query);
+        self.entries.iter()
+            .find(|e| e.backend == backend && e.query == query)
+            .filter(|e| max_age.is_none_or(|ttl| e.age() <= ttl))
+    }
+
+    /// Store a backend's results for a query, replacing any previous entry
+    ///
+    /// A bare echo of the query (pip's guess when its index is unreachable)
+    /// is not real data, so it is skipped and any earlier entry kept.
+    pub fn insert(&mut self, backend: &str, query: &str, results: Vec<PackageResult>) {
+        let query = normalize(query);
+        if !results.is_empty() && results.iter().all(|r| !r.has_details() && normalize(&r.name) == query) {
+            return;
+        }
+        self.entries.retain(|e| !(e.backend == backend && e.query == query));
+        self.entries.push(CacheEntry {
+            backend: backend.to_string(),
+            query,
+            cached_at: Utc::now(),
+            results,
+        });
+    }
+
+    /// Drop entries older than `retention`
+    pub fn prune(&mut self, retention: Duration) {
+        self.entries.retain(|e| e.age() <= retention);
+    }
+}
+
+fn normalize(query: &str) -> String {
+    query.trim().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_respects_ttl_and_normalizes_query() {
+        let mut cache = SearchCache::default();
+        cache.insert("apt", "RipGrep ", vec![PackageResult::new("ripgrep".into(), "apt".into()).with_version("14.1.0")]);
+
+        assert_eq!(cache.get("apt", "ripgrep", None).unwrap().results.len(), 1);
+        assert!(cache.get("npm", "ripgrep", None).is_none());
+
+        cache.entries[0].cached_at = Utc::now() - chrono::Duration::hours(2);
+        assert!(cache.get("apt", "ripgrep", Some(Duration::from_secs(3600))).is_none());
+        assert!(cache.get("apt", "ripgrep", None).is_some());
+
+        cache.prune(Duration::from_secs(3600));
+        assert!(cache.get("apt", "ripgrep", None).is_none());
+    }
+
+    #[test]
+    fn test_query_echo_is_not_cached() {
+        let mut cache = SearchCache::default();
+        cache.insert("pip", "black", vec![PackageResult::new("black".into(), "pip".into()).with_version("24.1.0")]);
+        cache.insert("pip", "black", vec![PackageResult::new("black".into(), "pip".into())]);
+        assert_eq!(cache.get("pip", "black", None).unwrap().results[0].version.as_deref(), Some("24.1.0"));
+
+        cache.insert("pip", "ruff", vec![PackageResult::new("ruff".into(), "pip".into())]);
+        assert!(cache.get("pip", "ruff", None).is_none());
+    }
+}
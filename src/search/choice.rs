@@ -0,0 +1,186 @@
+//! Choosing one backend to install from, given federated search results
+
+use std::cmp::Ordering;
+use super::{PackageResult, SearchAggregator};
+use crate::backends::BackendKind;
+use crate::config::InstallPolicy;
+
+/// The backends that have a package, best first, and why the first won
+#[derive(Debug, Clone)]
+pub struct BackendChoice {
+    /// One exact match per backend, ordered by policy then score
+    pub candidates: Vec<PackageResult>,
+    /// Human-readable reason the first candidate was picked
+    pub reason: String,
+}
+
+impl BackendChoice {
+    /// The winning candidate
+    pub fn best(&self) -> &PackageResult {
+        &self.candidates[0]
+    }
+}
+
+/// Pick the backend to install `query` from
+///
+/// Only exact-name matches (aliases included) that report a version or
+/// description count.
+/// Candidates are ordered by `policy`; the aggregator's score (backend
+/// priority, metadata) breaks ties. Returns `None` when nothing matches.
+pub fn choose_backend(
+    query: &str,
+    results: Vec<PackageResult>,
+    policy: InstallPolicy,
+    aggregator: &SearchAggregator,
+) -> Option<BackendChoice> {
+    let mut candidates: Vec<PackageResult> = Vec::new();
+    for mut result in results {
+        if !aggregator.is_exact_match(query, &result) || !result.has_details() {
+            continue;
+        }
+        aggregator.calculate_score(query, &mut result);
+        // Some backends list a name more than once (channels, arches)
+        match candidates.iter_mut().find(|c| c.source == result.source) {
+            Some(existing) if existing.score >= result.score => {}
+            Some(existing) => *existing = result,
+            None => candidates.push(result),
+        }
+    }
+    if candidates.is_empty() {
+        return None;
+    }
+
+    candidates.sort_by(|a, b| prefer(policy, a, b).then_with(|| a.cmp(b)));
+    let reason = explain(policy, &candidates);
+    Some(BackendChoice { candidates, reason })
+}
+
+/// Compare two version strings segment by segment
+///
+/// Numeric segments compare as numbers and anything else as text, so
+/// `1.10` sorts above `1.9`. A leading `v` and a Debian epoch (`1:`) are
+/// ignored since they mean nothing across backends.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    fn segments(version: &str) -> Vec<&str> {
+        let version = version.trim().trim_start_matches('v');
+        let version = match version.split_once(':') {
+            Some((epoch, rest)) if epoch.chars().all(|c| c.is_ascii_digit()) => rest,
+            _ => version,
+        };
+        version.split(|c: char| !c.is_ascii_alphanumeric()).filter(|s| !s.is_empty()).collect()
+    }
+
+    let (a, b) = (segments(a), segments(b));
+    for (x, y) in a.iter().zip(&b) {
+        let order = match (x.parse::<u64>(), y.parse::<u64>()) {
+            (Ok(x), Ok(y)) => x.cmp(&y),
+            _ => x.cmp(y),
+        };
+        if order != Ordering::Equal {
+            return order;
+        }
+    }
+    a.len().cmp(&b.len())
+}
+
+/// `Less` when the policy prefers `a` over `b`
+fn prefer(policy: InstallPolicy, a: &PackageResult, b: &PackageResult) -> Ordering {
+    match policy {
+        InstallPolicy::Newest => match (&a.version, &b.version) {
+            (Some(x), Some(y)) => compare_versions(y, x),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        },
+        _ => kind_rank(policy, &a.source).cmp(&kind_rank(policy, &b.source)),
+    }
+}
+
+/// Position of a backend's kind in the policy's order of preference
+fn kind_rank(policy: InstallPolicy, backend: &str) -> usize {
+    use BackendKind::*;
+    let order = match policy {
+        InstallPolicy::Native => [Language, System, Universal, Source],
+        _ => [System, Universal, Language, Source],
+    };
+    let kind = BackendKind::of(backend);
+    order.iter().position(|k| *k == kind).unwrap_or(order.len())
+}
+
+/// Explain why the first candidate beat the runner-up
+fn explain(policy: InstallPolicy, candidates: &[PackageResult]) -> String {
+    let best = &candidates[0];
+    let Some(next) = candidates.get(1) else {
+        return format!("only {} has a package named exactly '{}'", best.source, best.name);
+    };
+    if prefer(policy, best, next) == Ordering::Equal {
+        return format!("{} comes before {} in backends.priority", best.source, next.source);
+    }
+    match policy {
+        InstallPolicy::Newest => format!(
+            "{} has the newest version ({}; {} has {})",
+            best.source,
+            best.version.as_deref().unwrap_or("unknown"),
+            next.source,
+            next.version.as_deref().unwrap_or("no version listed"),
+        ),
+        _ => format!(
+            "{} is a {}, which install.policy = {} prefers over {} ({})",
+            best.source, BackendKind::of(&best.source), policy, next.source, BackendKind::of(&next.source),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(source: &str, name: &str, version: &str) -> PackageResult {
+        PackageResult::new(name.to_string(), source.to_string()).with_version(version)
+    }
+
+    fn sources(choice: &BackendChoice) -> Vec<&str> {
+        choice.candidates.iter().map(|c| c.source.as_str()).collect()
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.9.2"), Ordering::Greater);
+        assert_eq!(compare_versions("v0.14.0", "0.14.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1:2.39.2-1", "2.40.0"), Ordering::Less);
+        assert_eq!(compare_versions("3.1", "3.1.0"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_only_exact_matches_with_details() {
+        let aggregator = SearchAggregator::default();
+        let results = vec![
+            result("apt", "prettier-doc", "1.0"),
+            PackageResult::new("prettier".to_string(), "pip".to_string()),
+            result("npm", "Prettier", "3.1.0"),
+        ];
+        let choice = choose_backend("prettier", results, InstallPolicy::System, &aggregator).unwrap();
+        assert_eq!(sources(&choice), ["npm"]);
+        assert!(choice.reason.starts_with("only npm"));
+
+        assert!(choose_backend("jq", vec![result("apt", "jqp", "1.0")], InstallPolicy::System, &aggregator).is_none());
+    }
+
+    #[test]
+    fn test_policies() {
+        let aggregator = SearchAggregator::new(vec!["apt".into(), "snap".into(), "pip".into()]);
+        let results = || vec![result("pip", "httpie", "3.2.2"), result("snap", "httpie", "3.2.1"), result("apt", "httpie", "3.2.2")];
+
+        let system = choose_backend("httpie", results(), InstallPolicy::System, &aggregator).unwrap();
+        assert_eq!(sources(&system), ["apt", "snap", "pip"]);
+
+        let native = choose_backend("httpie", results(), InstallPolicy::Native, &aggregator).unwrap();
+        assert_eq!(native.best().source, "pip");
+        assert!(native.reason.contains("language package manager"));
+
+        // apt and pip tie on version, so backend priority decides
+        let newest = choose_backend("httpie", results(), InstallPolicy::Newest, &aggregator).unwrap();
+        assert_eq!(sources(&newest), ["apt", "pip", "snap"]);
+        assert_eq!(newest.reason, "apt comes before pip in backends.priority");
+    }
+}
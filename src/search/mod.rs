@@ -1,5 +1,9 @@
 //! Search module for federated package discovery
 
 mod aggregator;
+mod cache;
+mod choice;
 
-pub use aggregator::{SearchAggregator, PackageResult};
+pub use aggregator::{SearchAggregator, PackageResult, SearchOutcome, BackendSearchReport};
+pub use choice::{choose_backend, compare_versions, BackendChoice};
+pub use cache::{SearchCache, CacheEntry};
@@ -4,7 +4,13 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 #[cfg(unix)]
-use std::os::unix::fs::PermissionsExt;
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use super::ShimEntry;
+use crate::config::{load_config, ShimMode};
+use crate::error::OneInstallError;
+
+/// File name of the compiled shim shipped next to `1i`
+const NATIVE_SHIM: &str = "1i-shim";
 
 /// Get the shim directory path
 pub fn get_shim_dir() -> PathBuf {
@@ -28,64 +34,191 @@ pub fn ensure_shim_dir() -> std::io::Result<PathBuf> {
     Ok(dir)
 }
 
-/// Create a shim for a binary
-pub fn create_shim(binary_name: &str, target_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+/// Create the shim for a registry entry in the configured `shims.mode`
+pub fn create_shim(entry: &ShimEntry) -> Result<PathBuf, OneInstallError> {
+    match shim_mode() {
+        ShimMode::Script => create_script_shim(entry),
+        ShimMode::Native => create_native_shim(&entry.name),
+    }
+}
+
+/// Write a script shim with the target, environment and arguments baked in
+fn create_script_shim(entry: &ShimEntry) -> Result<PathBuf, OneInstallError> {
     let shim_dir = ensure_shim_dir()?;
+    let files = shim_files(&shim_dir, entry);
+    // A native shim may be a hardlink; writing through it would clobber 1i-shim
+    let _ = fs::remove_file(native_shim_path(&shim_dir, &entry.name));
     
-    #[cfg(windows)]
-    {
-        create_windows_shim(&shim_dir, binary_name, target_path)
+    for (path, script) in &files {
+        fs::write(path, script)?;
+        
+        // Make executable
+        #[cfg(unix)]
+        {
+            let mut perms = fs::metadata(path)?.permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(path, perms)?;
+        }
     }
     
-    #[cfg(not(windows))]
-    {
-        create_unix_shim(&shim_dir, binary_name, target_path)
+    Ok(files.into_iter().next().map(|(path, _)| path).unwrap_or(shim_dir))
+}
+
+/// Hardlink (or copy) `1i-shim` under the shim's name
+///
+/// The link carries no target: `1i-shim` looks itself up in the registry
+/// when run, so retargeting it only takes a registry edit.
+fn create_native_shim(binary_name: &str) -> Result<PathBuf, OneInstallError> {
+    let source = native_shim_binary()?;
+    let shim_dir = ensure_shim_dir()?;
+    let shim_path = native_shim_path(&shim_dir, binary_name);
+    
+    remove_shim(binary_name);
+    if fs::hard_link(&source, &shim_path).is_err() {
+        // Different filesystem: fall back to a copy
+        fs::copy(&source, &shim_path)?;
+    }
+    Ok(shim_path)
+}
+
+/// Whether a shim's files exist and match what `create_shim` would write
+///
+/// Native shims read the registry when run, so any `1i-shim` copy is current.
+pub fn shim_is_current(entry: &ShimEntry) -> bool {
+    let shim_dir = get_shim_dir();
+    match shim_mode() {
+        ShimMode::Script => shim_files(&shim_dir, entry).iter()
+            .all(|(path, script)| fs::read_to_string(path).is_ok_and(|content| content == *script)),
+        ShimMode::Native => native_shim_binary()
+            .is_ok_and(|source| same_file_contents(&source, &native_shim_path(&shim_dir, &entry.name))),
+    }
+}
+
+/// Delete a shim's file(s), in either mode, from the shim directory
+pub fn remove_shim(binary_name: &str) {
+    let shim_dir = get_shim_dir();
+    for path in script_paths(&shim_dir, binary_name) {
+        let _ = fs::remove_file(path);
+    }
+    let _ = fs::remove_file(native_shim_path(&shim_dir, binary_name));
+}
+
+/// The `1i-shim` executable installed alongside the running `1i`
+pub fn native_shim_binary() -> Result<PathBuf, OneInstallError> {
+    let path = std::env::current_exe()?
+        .with_file_name(format!("{}{}", NATIVE_SHIM, std::env::consts::EXE_SUFFIX));
+    if path.is_file() {
+        Ok(path)
+    } else {
+        Err(OneInstallError::Unsupported(format!(
+            "{} not found; reinstall 1install or set shims.mode to script", path.display()
+        )))
+    }
+}
+
+/// The configured shim mode (scripts if the config can't be read)
+fn shim_mode() -> ShimMode {
+    load_config().map(|config| config.shims.mode).unwrap_or_default()
+}
+
+/// Where a native shim for a name lives
+fn native_shim_path(shim_dir: &Path, binary_name: &str) -> PathBuf {
+    shim_dir.join(format!("{}{}", binary_name, std::env::consts::EXE_SUFFIX))
+}
+
+/// Whether two paths are the same file, or identical copies
+fn same_file_contents(a: &Path, b: &Path) -> bool {
+    let (Ok(meta_a), Ok(meta_b)) = (fs::metadata(a), fs::metadata(b)) else {
+        return false;
+    };
+    #[cfg(unix)]
+    if meta_a.dev() == meta_b.dev() && meta_a.ino() == meta_b.ino() {
+        return true;
     }
+    meta_a.len() == meta_b.len() && fs::read(a).ok() == fs::read(b).ok()
 }
 
-/// Create a Unix shell script shim
+/// Where a script shim's file(s) for a name live
+fn script_paths(shim_dir: &Path, binary_name: &str) -> Vec<PathBuf> {
+    if cfg!(windows) {
+        vec![shim_dir.join(format!("{}.cmd", binary_name)), shim_dir.join(format!("{}.ps1", binary_name))]
+    } else {
+        vec![shim_dir.join(binary_name)]
+    }
+}
+
+/// Unix shell script shim
 #[cfg(not(windows))]
-fn create_unix_shim(shim_dir: &Path, binary_name: &str, target_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let shim_path = shim_dir.join(binary_name);
-    
-    let script = format!(r#"#!/bin/sh
-# 1install shim for {}
-exec "{}" "$@"
-"#, binary_name, target_path.display());
-    
-    fs::write(&shim_path, script)?;
-    
-    // Make executable
-    let mut perms = fs::metadata(&shim_path)?.permissions();
-    perms.set_mode(0o755);
-    fs::set_permissions(&shim_path, perms)?;
+fn shim_files(shim_dir: &Path, entry: &ShimEntry) -> Vec<(PathBuf, String)> {
+    let mut script = format!("#!/bin/sh\n# 1install shim for {}\n", entry.name);
+    for (key, value) in &entry.env {
+        script.push_str(&format!("export {}={}\n", key, sh_quote(value)));
+    }
+    if let Some(dir) = entry.cwd.resolve(&entry.target) {
+        script.push_str(&format!("cd {} || exit 1\n", sh_quote(&dir.to_string_lossy())));
+    }
+    let args: String = entry.args.iter().map(|arg| format!("{} ", sh_quote(arg))).collect();
+    script.push_str(&format!("exec \"{}\" {}\"$@\"\n", entry.target.display(), args));
     
-    Ok(shim_path)
+    script_paths(shim_dir, &entry.name).into_iter().zip([script]).collect()
 }
 
-/// Create a Windows batch file shim
+/// Windows batch file and PowerShell shims (both, for maximum compatibility)
 #[cfg(windows)]
-fn create_windows_shim(shim_dir: &Path, binary_name: &str, target_path: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
-    // Create both .cmd and .ps1 shims for maximum compatibility
-    let cmd_path = shim_dir.join(format!("{}.cmd", binary_name));
-    let ps1_path = shim_dir.join(format!("{}.ps1", binary_name));
-    
-    // Batch file shim
-    let cmd_script = format!(r#"@echo off
-rem 1install shim for {}
-"{}" %*
-"#, binary_name, target_path.display());
-    
-    fs::write(&cmd_path, cmd_script)?;
+fn shim_files(shim_dir: &Path, entry: &ShimEntry) -> Vec<(PathBuf, String)> {
+    let cwd = entry.cwd.resolve(&entry.target);
+    let isolated = !entry.env.is_empty() || cwd.is_some();
     
-    // PowerShell shim
-    let ps1_script = format!(r#"# 1install shim for {}
-& "{}" $args
-"#, binary_name, target_path.display());
+    // Batch file shim; setlocal keeps env and cwd changes out of the caller's shell
+    let mut cmd_script = format!("@echo off\nrem 1install shim for {}\n", entry.name);
+    if isolated {
+        cmd_script.push_str("setlocal\n");
+    }
+    for (key, value) in &entry.env {
+        cmd_script.push_str(&format!("set \"{}={}\"\n", key, value));
+    }
+    if let Some(ref dir) = cwd {
+        cmd_script.push_str(&format!("cd /d \"{}\"\n", dir.display()));
+    }
+    let args: String = entry.args.iter().map(|arg| format!("\"{}\" ", arg)).collect();
+    cmd_script.push_str(&format!("\"{}\" {}%*\n", entry.target.display(), args));
     
-    fs::write(&ps1_path, ps1_script)?;
+    // PowerShell shim; env and location are session-wide, so restore them afterwards
+    let mut ps1_script = format!("# 1install shim for {}\n", entry.name);
+    let args: String = entry.args.iter().map(|arg| format!("{} ", ps_quote(arg))).collect();
+    let call = format!("& \"{}\" {}$args", entry.target.display(), args);
+    if isolated {
+        ps1_script.push_str("$saved = @{}\n");
+        for (key, value) in &entry.env {
+            ps1_script.push_str(&format!(
+                "$saved[{0}] = $env:{1}; $env:{1} = {2}\n", ps_quote(key), key, ps_quote(value)
+            ));
+        }
+        if let Some(ref dir) = cwd {
+            ps1_script.push_str(&format!("Push-Location {}\n", ps_quote(&dir.to_string_lossy())));
+        }
+        ps1_script.push_str(&format!("try {{ {} }} finally {{\n", call));
+        if cwd.is_some() {
+            ps1_script.push_str("    Pop-Location\n");
+        }
+        ps1_script.push_str("    foreach ($key in $saved.Keys) { [Environment]::SetEnvironmentVariable($key, $saved[$key]) }\n}\n");
+    } else {
+        ps1_script.push_str(&format!("{}\n", call));
+    }
     
-    Ok(cmd_path)
+    script_paths(shim_dir, &entry.name).into_iter().zip([cmd_script, ps1_script]).collect()
+}
+
+/// Quote a value for a POSIX shell
+#[cfg(not(windows))]
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Quote a value as a PowerShell literal string
+#[cfg(windows)]
+fn ps_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
 }
 
 /// Get the path setup instruction for the user's shell
@@ -131,6 +264,26 @@ fish (~/.config/fish/config.fish):
 mod tests {
     use super::*;
     
+    #[test]
+    #[cfg(not(windows))]
+    fn test_script_applies_env_args_and_cwd() {
+        let mut registry = super::super::ShimRegistry::default();
+        let entry = registry.add("rg".to_string(), PathBuf::from("/usr/bin/rg"), "apt".to_string());
+        let (_, plain) = &shim_files(Path::new("/shims"), entry)[0];
+        assert_eq!(plain, "#!/bin/sh\n# 1install shim for rg\nexec \"/usr/bin/rg\" \"$@\"\n");
+        
+        let entry = registry.get_mut("rg").unwrap();
+        entry.env.insert("RIPGREP_CONFIG_PATH".to_string(), "/home/u/it's.rc".to_string());
+        entry.args = vec!["--smart-case".to_string()];
+        entry.cwd = "target".parse().unwrap();
+        let (path, script) = &shim_files(Path::new("/shims"), entry)[0];
+        
+        assert_eq!(path, Path::new("/shims/rg"));
+        assert!(script.contains("export RIPGREP_CONFIG_PATH='/home/u/it'\\''s.rc'\n"));
+        assert!(script.contains("cd '/usr/bin' || exit 1\n"));
+        assert!(script.ends_with("exec \"/usr/bin/rg\" '--smart-case' \"$@\"\n"));
+    }
+    
     #[test]
     fn test_get_shim_dir() {
         let dir = get_shim_dir();
@@ -1,7 +1,12 @@
 //! Shim management module
 
 mod generator;
+mod refresh;
 mod registry;
 
-pub use generator::{create_shim, get_shim_dir, ensure_shim_dir, get_path_instruction};
-pub use registry::{ShimRegistry, ShimEntry};
+pub use generator::{
+    create_shim, get_shim_dir, ensure_shim_dir, get_path_instruction, remove_shim, shim_is_current,
+    native_shim_binary,
+};
+pub use refresh::{refresh_shims, ShimChange, ShimChangeKind};
+pub use registry::{ShimRegistry, ShimEntry, WorkingDir};
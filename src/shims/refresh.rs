@@ -0,0 +1,77 @@
+//! Rebuilding shims whose targets moved, vanished or whose files went stale
+
+use serde::Serialize;
+use std::path::PathBuf;
+use super::{create_shim, remove_shim, shim_is_current, ShimEntry, ShimRegistry};
+use crate::backends::is_dry_run;
+use crate::error::OneInstallError;
+
+/// What a refresh did to one shim
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShimChangeKind {
+    /// Same target, but the shim file was missing or out of date
+    Regenerated,
+    /// The target moved and the shim now points at its new location
+    Retargeted,
+    /// The target is gone and nothing replaces it
+    Removed,
+}
+
+/// One shim a refresh touched
+#[derive(Debug, Clone, Serialize)]
+pub struct ShimChange {
+    pub name: String,
+    pub change: ShimChangeKind,
+    /// Target before the refresh
+    pub previous: PathBuf,
+    /// Target after the refresh (none when removed)
+    pub target: Option<PathBuf>,
+}
+
+/// Re-resolve every shim's target and rewrite or remove shims to match
+///
+/// Shims whose target still exists keep it, as do pinned shims; for the
+/// rest `resolve` looks for where the binary went. Returns the shims that
+/// changed, by name. Changes are applied to `registry` in memory only and
+/// the caller decides whether to save it; in dry run the changes are
+/// computed but neither the registry nor any shim file is written.
+pub fn refresh_shims(
+    registry: &mut ShimRegistry,
+    resolve: impl Fn(&ShimEntry) -> Option<PathBuf>,
+) -> Result<Vec<ShimChange>, OneInstallError> {
+    let mut entries: Vec<ShimEntry> = registry.list().cloned().collect();
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    
+    let mut changes = Vec::new();
+    for entry in entries {
+        let target = if entry.pinned || entry.target.exists() { Some(entry.target.clone()) } else { resolve(&entry) };
+        let change = match target {
+            None => {
+                registry.remove(&entry.name);
+                if !is_dry_run() {
+                    remove_shim(&entry.name);
+                }
+                ShimChangeKind::Removed
+            }
+            Some(ref target) if *target != entry.target => {
+                registry.retarget(&entry.name, target.clone());
+                let updated = ShimEntry { target: target.clone(), ..entry.clone() };
+                // Native shims read the registry, so only script shims need rewriting
+                if !is_dry_run() && !shim_is_current(&updated) {
+                    create_shim(&updated)?;
+                }
+                ShimChangeKind::Retargeted
+            }
+            Some(_) if !shim_is_current(&entry) => {
+                if !is_dry_run() {
+                    create_shim(&entry)?;
+                }
+                ShimChangeKind::Regenerated
+            }
+            Some(_) => continue,
+        };
+        changes.push(ShimChange { name: entry.name, change, previous: entry.target, target });
+    }
+    Ok(changes)
+}
@@ -1,10 +1,13 @@
 //! Shim registry for tracking installed shims
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use chrono::{DateTime, Utc};
+use crate::error::OneInstallError;
 
 /// A single shim entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +20,85 @@ pub struct ShimEntry {
     pub installed_by: String,
     /// When the shim was created
     pub created_at: DateTime<Utc>,
+    /// Refresh leaves the target alone
+    #[serde(default)]
+    pub pinned: bool,
+    /// Environment variables set before running the target
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Arguments passed to the target before the caller's
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Directory the target runs in
+    #[serde(default, skip_serializing_if = "WorkingDir::is_inherit")]
+    pub cwd: WorkingDir,
+}
+
+/// Directory a shim runs its target in
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum WorkingDir {
+    /// Wherever the shim was run from
+    #[default]
+    Inherit,
+    /// The directory containing the target binary
+    Target,
+    /// A fixed directory
+    Path(PathBuf),
+}
+
+impl WorkingDir {
+    fn is_inherit(&self) -> bool {
+        *self == WorkingDir::Inherit
+    }
+    
+    /// Directory to change to before running `target`, if any
+    pub fn resolve(&self, target: &Path) -> Option<PathBuf> {
+        match self {
+            WorkingDir::Inherit => None,
+            WorkingDir::Target => target.parent().map(Path::to_path_buf),
+            WorkingDir::Path(path) => Some(path.clone()),
+        }
+    }
+}
+
+impl fmt::Display for WorkingDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkingDir::Inherit => f.write_str("inherit"),
+            WorkingDir::Target => f.write_str("target"),
+            WorkingDir::Path(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+impl FromStr for WorkingDir {
+    type Err = OneInstallError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "inherit" => Ok(WorkingDir::Inherit),
+            "target" => Ok(WorkingDir::Target),
+            path if Path::new(path).is_absolute() => Ok(WorkingDir::Path(PathBuf::from(path))),
+            _ => Err(OneInstallError::InvalidInput(format!(
+                "Invalid working directory: '{}' (expected inherit, target or an absolute path)", value
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for WorkingDir {
+    type Error = OneInstallError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<WorkingDir> for String {
+    fn from(cwd: WorkingDir) -> Self {
+        cwd.to_string()
+    }
 }
 
 /// Registry of all shims
@@ -27,7 +109,7 @@ pub struct ShimRegistry {
 
 impl ShimRegistry {
     /// Load the registry from disk
-    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load() -> Result<Self, OneInstallError> {
         let path = Self::registry_path();
         
         if path.exists() {
@@ -40,7 +122,7 @@ impl ShimRegistry {
     }
     
     /// Save the registry to disk
-    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn save(&self) -> Result<(), OneInstallError> {
         let path = Self::registry_path();
         
         // Ensure parent directory exists
@@ -63,14 +145,44 @@ impl ShimRegistry {
     }
     
     /// Add a shim to the registry
-    pub fn add(&mut self, name: String, target: PathBuf, installed_by: String) {
+    ///
+    /// Re-adding an existing name keeps its pin, environment, arguments and
+    /// working directory.
+    pub fn add(&mut self, name: String, target: PathBuf, installed_by: String) -> &ShimEntry {
+        let previous = self.shims.remove(&name);
         let entry = ShimEntry {
             name: name.clone(),
             target,
             installed_by,
             created_at: Utc::now(),
+            pinned: previous.as_ref().is_some_and(|p| p.pinned),
+            env: previous.as_ref().map(|p| p.env.clone()).unwrap_or_default(),
+            args: previous.as_ref().map(|p| p.args.clone()).unwrap_or_default(),
+            cwd: previous.map(|p| p.cwd).unwrap_or_default(),
         };
-        self.shims.insert(name, entry);
+        self.shims.entry(name).or_insert(entry)
+    }
+    
+    /// Point an existing shim at a new binary
+    pub fn retarget(&mut self, name: &str, target: PathBuf) -> Option<&ShimEntry> {
+        let entry = self.shims.get_mut(name)?;
+        entry.target = target;
+        Some(entry)
+    }
+    
+    /// Move a shim to a new name, keeping its target and settings
+    pub fn rename(&mut self, from: &str, to: &str) -> Option<&ShimEntry> {
+        let mut entry = self.shims.remove(from)?;
+        entry.name = to.to_string();
+        self.shims.insert(to.to_string(), entry);
+        self.shims.get(to)
+    }
+    
+    /// Pin or unpin a shim
+    pub fn set_pinned(&mut self, name: &str, pinned: bool) -> Option<&ShimEntry> {
+        let entry = self.shims.get_mut(name)?;
+        entry.pinned = pinned;
+        Some(entry)
     }
     
     /// Remove a shim from the registry
@@ -83,6 +195,11 @@ impl ShimRegistry {
         self.shims.get(name)
     }
     
+    /// Get a shim by name for editing
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut ShimEntry> {
+        self.shims.get_mut(name)
+    }
+    
     /// List all shims
     pub fn list(&self) -> impl Iterator<Item = &ShimEntry> {
         self.shims.values()
@@ -116,4 +233,45 @@ mod tests {
         assert_eq!(entry.name, "test");
         assert_eq!(entry.installed_by, "apt");
     }
+    
+    #[test]
+    fn test_registry_retarget() {
+        let mut registry = ShimRegistry::default();
+        registry.add("rg".to_string(), PathBuf::from("/usr/bin/rg"), "apt".to_string());
+        
+        registry.retarget("rg", PathBuf::from("/usr/local/bin/rg"));
+        assert_eq!(registry.get("rg").unwrap().target, PathBuf::from("/usr/local/bin/rg"));
+        assert_eq!(registry.get("rg").unwrap().installed_by, "apt");
+        assert!(registry.retarget("fd", PathBuf::from("/usr/bin/fd")).is_none());
+    }
+    
+    #[test]
+    fn test_registry_rename_keeps_pin() {
+        let mut registry = ShimRegistry::default();
+        registry.add("fdfind".to_string(), PathBuf::from("/usr/bin/fdfind"), "apt".to_string());
+        registry.set_pinned("fdfind", true);
+        
+        let entry = registry.rename("fdfind", "fd").unwrap();
+        assert_eq!(entry.name, "fd");
+        assert!(entry.pinned);
+        assert!(registry.get("fdfind").is_none());
+    }
+    
+    #[test]
+    fn test_readd_keeps_settings() {
+        let mut registry = ShimRegistry::default();
+        registry.add("node".to_string(), PathBuf::from("/usr/bin/node"), "apt".to_string());
+        let entry = registry.get_mut("node").unwrap();
+        entry.env.insert("NODE_OPTIONS".to_string(), "--max-old-space-size=4096".to_string());
+        entry.cwd = "target".parse().unwrap();
+        
+        let entry = registry.add("node".to_string(), PathBuf::from("/opt/node/bin/node"), "brew".to_string());
+        assert_eq!(entry.env["NODE_OPTIONS"], "--max-old-space-size=4096");
+        assert_eq!(entry.cwd.resolve(&entry.target), Some(PathBuf::from("/opt/node/bin")));
+        
+        let toml = toml::to_string(&registry).unwrap();
+        let parsed: ShimRegistry = toml::from_str(&toml).unwrap();
+        assert_eq!(parsed.get("node").unwrap().cwd, WorkingDir::Target);
+        assert!("relative/dir".parse::<WorkingDir>().is_err());
+    }
 }
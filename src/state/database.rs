@@ -0,0 +1,193 @@
+//! Record of every package installed through 1install
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+use crate::config::get_data_dir;
+use crate::backends::backend_matches;
+use crate::error::OneInstallError;
+
+/// What 1install knows about one installed package
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstallRecord {
+    /// Package name as the backend knows it (after alias translation)
+    pub name: String,
+    /// Package name as requested by the user (empty in records that predate it)
+    #[serde(default)]
+    pub requested: String,
+    /// Backend that installed it
+    pub backend: String,
+    /// Version reported by the backend after installing
+    pub version: Option<String>,
+    /// Binaries located after installing
+    #[serde(default)]
+    pub binaries: Vec<PathBuf>,
+    /// Shims created for this package
+    #[serde(default)]
+    pub shims: Vec<String>,
+    /// Native commands that performed the install
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// When the package was installed
+    pub installed_at: DateTime<Utc>,
+    /// When the package was last updated through 1install
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl InstallRecord {
+    /// Create a record for a package installed just now
+    pub fn new(name: impl Into<String>, backend: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            requested: name.clone(),
+            name,
+            backend: backend.into(),
+            version: None,
+            binaries: Vec::new(),
+            shims: Vec::new(),
+            commands: Vec::new(),
+            installed_at: Utc::now(),
+            updated_at: None,
+        }
+    }
+
+    /// The name the user asked for, when it differs from the backend's
+    pub fn requested_as(&self) -> Option<&str> {
+        Some(self.requested.as_str()).filter(|r| !r.is_empty() && *r != self.name)
+    }
+}
+
+/// Persistent install state, stored as TOML under the data dir
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StateDb {
+    #[serde(default, rename = "package")]
+    packages: Vec<InstallRecord>,
+}
+
+impl StateDb {
+    /// Load the state from disk
+    pub fn load() -> Result<Self, OneInstallError> {
+        let path = Self::state_path();
+
+        if path.exists() {
+            let content = fs::read_to_string(&path)?;
+            let state: StateDb = toml::from_str(&content)?;
+            Ok(state)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Save the state to disk
+    pub fn save(&self) -> Result<(), OneInstallError> {
+        let path = Self::state_path();
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = toml::to_string_pretty(self)?;
+        fs::write(&path, content)?;
+        Ok(())
+    }
+
+    /// Get the state file path
+    fn state_path() -> PathBuf {
+        get_data_dir().join("state.toml")
+    }
+
+    /// Add or replace the record for a (name, backend) pair
+    pub fn record(&mut self, record: InstallRecord) {
+        self.remove(&record.name, &record.backend);
+        self.packages.push(record);
+    }
+
+    /// Every record for a package name, across backends
+    ///
+    /// Matches the requested name as well as the backend's.
+    pub fn find(&self, name: &str) -> Vec<&InstallRecord> {
+        self.packages.iter().filter(|r| r.name == name || r.requested == name).collect()
+    }
+
+    /// The name the user asked for a package by, when it differs from the backend's
+    pub fn requested_as(&self, name: &str, backend: &str) -> Option<&str> {
+        self.packages.iter()
+            .find(|r| r.name == name && backend_matches(&r.backend, backend))
+            .and_then(InstallRecord::requested_as)
+    }
+
+    /// The record for a package installed by a specific backend
+    pub fn get_mut(&mut self, name: &str, backend: &str) -> Option<&mut InstallRecord> {
+        self.packages.iter_mut().find(|r| r.name == name && backend_matches(&r.backend, backend))
+    }
+
+    /// Remove the record for a (name, backend) pair
+    pub fn remove(&mut self, name: &str, backend: &str) -> Option<InstallRecord> {
+        let index = self.packages.iter().position(|r| r.name == name && backend_matches(&r.backend, backend))?;
+        Some(self.packages.remove(index))
+    }
+
+    /// All records, oldest install first
+    pub fn list(&self) -> impl Iterator<Item = &InstallRecord> {
+        self.packages.iter()
+    }
+
+    /// Find the backend that installed a package, if exactly one did
+    ///
+    /// Fails when several backends installed the same name, since the
+    /// caller then has to pick one with `--backend`.
+    pub fn backend_for(&self, name: &str) -> Result<Option<&str>, OneInstallError> {
+        match self.find(name).as_slice() {
+            [] => Ok(None),
+            [record] => Ok(Some(&record.backend)),
+            records => Err(OneInstallError::InvalidInput(format!(
+                "{} was installed by several backends ({}); pick one with --backend",
+                name,
+                records.iter().map(|r| r.backend.as_str()).collect::<Vec<_>>().join(", ")
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_replace_and_backend_lookup() {
+        let mut state = StateDb::default();
+        state.record(InstallRecord::new("jq", "apt"));
+        assert_eq!(state.backend_for("jq").unwrap(), Some("apt"));
+        assert_eq!(state.backend_for("rg").unwrap(), None);
+
+        let mut again = InstallRecord::new("jq", "apt");
+        again.version = Some("1.7".into());
+        state.record(again);
+        assert_eq!(state.find("jq").len(), 1);
+        assert_eq!(state.find("jq")[0].version.as_deref(), Some("1.7"));
+
+        state.record(InstallRecord::new("jq", "brew"));
+        assert!(state.backend_for("jq").is_err());
+
+        assert!(state.remove("jq", "brew").is_some());
+        assert_eq!(state.backend_for("jq").unwrap(), Some("apt"));
+    }
+
+    #[test]
+    fn test_requested_name() {
+        let mut state: StateDb = toml::from_str(r#"
+            [[package]]
+            name = "ripgrep"
+            backend = "apt"
+            installed_at = "2024-01-01T00:00:00Z"
+        "#).unwrap();
+        assert_eq!(state.requested_as("ripgrep", "apt"), None);
+
+        let mut fd = InstallRecord::new("fd-find", "apt");
+        fd.requested = "fd".into();
+        state.record(fd);
+        assert_eq!(state.requested_as("fd-find", "apt"), Some("fd"));
+        assert_eq!(state.backend_for("fd").unwrap(), Some("apt"));
+    }
+}
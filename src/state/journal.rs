@@ -0,0 +1,171 @@
+//! Append-only journal of every change 1install made
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+use crate::config::get_data_dir;
+use crate::error::OneInstallError;
+
+/// Kind of change recorded in the journal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalAction {
+    Install,
+    Update,
+    Uninstall,
+}
+
+impl fmt::Display for JournalAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JournalAction::Install => write!(f, "install"),
+            JournalAction::Update => write!(f, "update"),
+            JournalAction::Uninstall => write!(f, "uninstall"),
+        }
+    }
+}
+
+/// One completed change
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Sequential id, starting at 1
+    pub id: u64,
+    /// When the change finished
+    pub timestamp: DateTime<Utc>,
+    /// What was done
+    pub action: JournalAction,
+    /// Package name
+    pub package: String,
+    /// Backend that did it
+    pub backend: String,
+    /// Installed version before the change (`None` if not installed)
+    pub before: Option<String>,
+    /// Installed version after the change (`None` if removed or unknown)
+    pub after: Option<String>,
+    /// Id of the entry this change reversed, for `1i undo`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undoes: Option<u64>,
+}
+
+/// The journal file, one JSON entry per line
+#[derive(Debug, Default)]
+pub struct Journal {
+    entries: Vec<JournalEntry>,
+}
+
+impl Journal {
+    /// Load every entry from disk
+    pub fn load() -> Result<Self, OneInstallError> {
+        let path = Self::journal_path();
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        let entries = content.lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<JournalEntry>, _>>()?;
+        Ok(Self { entries })
+    }
+
+    /// Get the journal file path
+    fn journal_path() -> PathBuf {
+        get_data_dir().join("journal.jsonl")
+    }
+
+    /// Append a change, assigning the next id, and flush it to disk
+    pub fn append(
+        &mut self,
+        action: JournalAction,
+        package: &str,
+        backend: &str,
+        before: Option<String>,
+        after: Option<String>,
+        undoes: Option<u64>,
+    ) -> Result<&JournalEntry, OneInstallError> {
+        let entry = JournalEntry {
+            id: self.entries.last().map_or(1, |e| e.id + 1),
+            timestamp: Utc::now(),
+            action,
+            package: package.to_string(),
+            backend: backend.to_string(),
+            before,
+            after,
+            undoes,
+        };
+
+        let path = Self::journal_path();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.sync_data()?;
+
+        self.entries.push(entry);
+        Ok(self.entries.last().expect("entry was just pushed"))
+    }
+
+    /// All entries, oldest first
+    pub fn entries(&self) -> &[JournalEntry] {
+        &self.entries
+    }
+
+    /// Find an entry by id
+    pub fn get(&self, id: u64) -> Option<&JournalEntry> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    /// The entry that reversed `id`, if any
+    pub fn undone_by(&self, id: u64) -> Option<&JournalEntry> {
+        self.entries.iter().find(|e| e.undoes == Some(id))
+    }
+
+    /// The most recent change that has not been undone and is not itself an undo
+    pub fn last_undoable(&self) -> Option<&JournalEntry> {
+        self.entries.iter().rev()
+            .find(|e| e.undoes.is_none() && self.undone_by(e.id).is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: u64, undoes: Option<u64>) -> JournalEntry {
+        JournalEntry {
+            id,
+            timestamp: Utc::now(),
+            action: JournalAction::Install,
+            package: "jq".to_string(),
+            backend: "apt".to_string(),
+            before: None,
+            after: Some("1.7".to_string()),
+            undoes,
+        }
+    }
+
+    #[test]
+    fn test_last_undoable_skips_undone_entries() {
+        let journal = Journal { entries: vec![entry(1, None), entry(2, None), entry(3, Some(2))] };
+
+        assert_eq!(journal.last_undoable().map(|e| e.id), Some(1));
+        assert_eq!(journal.undone_by(2).map(|e| e.id), Some(3));
+        assert!(journal.undone_by(1).is_none());
+    }
+
+    #[test]
+    fn test_entry_json_roundtrip() {
+        let original = entry(7, None);
+        let line = serde_json::to_string(&original).unwrap();
+
+        assert!(!line.contains("undoes"));
+        assert!(!line.contains('\n'));
+        assert_eq!(serde_json::from_str::<JournalEntry>(&line).unwrap(), original);
+    }
+}
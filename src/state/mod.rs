@@ -0,0 +1,7 @@
+//! Persistent record of what 1install has installed and changed
+
+mod database;
+mod journal;
+
+pub use database::{StateDb, InstallRecord};
+pub use journal::{Journal, JournalEntry, JournalAction};
@@ -69,7 +69,11 @@ impl TelemetryClient {
             "event": event
         });
 
-        tokio::spawn(async move {
+        // Callers off the runtime (e.g. worker threads) still get the local log
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            return;
+        };
+        runtime.spawn(async move {
             let client = reqwest::Client::builder()
                 .timeout(std::time::Duration::from_secs(2))
                 .build();
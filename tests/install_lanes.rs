@@ -0,0 +1,43 @@
+//! Multi-package installs run each backend on its own thread; these drive
+//! the real `1i` binary against a scratch HOME in dry run.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn scratch_home(name: &str) -> PathBuf {
+    let home = std::env::temp_dir().join(format!("1install-test-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&home);
+    fs::create_dir_all(&home).unwrap();
+    home
+}
+
+fn run_1i(home: &Path, args: &[&str]) -> (bool, String) {
+    let output = Command::new(env!("CARGO_BIN_EXE_1i"))
+        .args(args)
+        .env("HOME", home)
+        .env("USERPROFILE", home)
+        .output()
+        .unwrap();
+    let text = format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    (output.status.success(), text)
+}
+
+#[test]
+fn test_multi_lane_install_with_telemetry() {
+    let home = scratch_home("telemetry");
+    let (ok, output) = run_1i(&home, &["config", "set", "telemetry.enabled", "true"]);
+    assert!(ok, "{}", output);
+
+    // cargo and git never need sudo, so each gets its own lane
+    let (_, output) = run_1i(&home, &[
+        "install", "cargo:ripgrep", "git:https://github.com/BurntSushi/ripgrep", "--dry-run",
+    ]);
+    assert!(!output.contains("panicked"), "{}", output);
+
+    let log = fs::read_to_string(home.join(".config").join("1install").join("telemetry.log")).unwrap();
+    assert!(log.contains("InstallStarted { backend: \"cargo\" }"), "{}", log);
+    assert!(log.contains("InstallFinished { backend: \"cargo\""), "{}", log);
+
+    let _ = fs::remove_dir_all(&home);
+}